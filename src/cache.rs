@@ -0,0 +1,191 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use anyhow::{Context, anyhow};
+use async_trait::async_trait;
+use tokio::fs;
+
+// 缓存存储后端。key 为 `/` 分隔的相对路径（如 `state/index_v2.csv`）
+#[async_trait]
+pub trait CacheStore: Send + Sync {
+    async fn read(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>>;
+    async fn write(&self, key: &str, data: &[u8]) -> anyhow::Result<()>;
+    async fn remove(&self, key: &str) -> anyhow::Result<()>;
+    // 列出某目录下的直接子项，返回完整 key
+    async fn list(&self, dir: &str) -> anyhow::Result<Vec<String>>;
+
+    // 下载产物需要以文件形式交给调用方；不落盘的实现返回 None
+    fn local_path(&self, _key: &str) -> Option<PathBuf> {
+        None
+    }
+}
+
+fn normalize_key(key: &str) -> anyhow::Result<String> {
+    let parts = key
+        .split(['/', '\\'])
+        .filter(|part| !part.is_empty() && *part != ".")
+        .collect::<Vec<_>>();
+    if parts.contains(&"..") {
+        return Err(anyhow!("invalid cache key `{key}`"));
+    }
+    Ok(parts.join("/"))
+}
+
+// 文件系统实现，所有 key 均位于 root 之下
+#[derive(Debug, Clone)]
+pub struct FsCacheStore {
+    root: PathBuf,
+}
+
+impl FsCacheStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn path(&self, key: &str) -> anyhow::Result<PathBuf> {
+        Ok(self.root.join(normalize_key(key)?))
+    }
+}
+
+#[async_trait]
+impl CacheStore for FsCacheStore {
+    async fn read(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let path = self.path(key)?;
+        match fs::read(&path).await {
+            Ok(data) => Ok(Some(data)),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err).with_context(|| format!("failed to read {}", path.display())),
+        }
+    }
+
+    // 先写临时文件再 rename，避免中途崩溃留下半截文件
+    async fn write(&self, key: &str, data: &[u8]) -> anyhow::Result<()> {
+        let path = self.path(key)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .await
+                .with_context(|| format!("failed to create directory {}", dir.display()))?;
+        }
+        let mut tmp_path = path.clone().into_os_string();
+        tmp_path.push(".part");
+        let tmp_path = PathBuf::from(tmp_path);
+        fs::write(&tmp_path, data)
+            .await
+            .with_context(|| format!("failed to write {}", tmp_path.display()))?;
+        fs::rename(&tmp_path, &path)
+            .await
+            .with_context(|| format!("failed to move {}", path.display()))
+    }
+
+    async fn remove(&self, key: &str) -> anyhow::Result<()> {
+        let path = self.path(key)?;
+        let result = match fs::metadata(&path).await {
+            Ok(meta) if meta.is_dir() => fs::remove_dir_all(&path).await,
+            Ok(_) => fs::remove_file(&path).await,
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err).with_context(|| format!("failed to remove {}", path.display())),
+        }
+    }
+
+    async fn list(&self, dir: &str) -> anyhow::Result<Vec<String>> {
+        let dir_key = normalize_key(dir)?;
+        let path = self.path(&dir_key)?;
+        let mut entries = match fs::read_dir(&path).await {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => {
+                return Err(err).with_context(|| format!("failed to list {}", path.display()));
+            }
+        };
+
+        let mut keys = Vec::new();
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .with_context(|| format!("failed to list {}", path.display()))?
+        {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            keys.push(if dir_key.is_empty() {
+                name
+            } else {
+                format!("{dir_key}/{name}")
+            });
+        }
+        keys.sort();
+        Ok(keys)
+    }
+
+    fn local_path(&self, key: &str) -> Option<PathBuf> {
+        self.path(key).ok()
+    }
+}
+
+// 内存实现，便于测试及无文件系统的环境
+#[derive(Debug, Default)]
+pub struct MemoryCacheStore {
+    entries: Mutex<HashMap<String, Vec<u8>>>,
+}
+
+impl MemoryCacheStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl CacheStore for MemoryCacheStore {
+    async fn read(&self, key: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let key = normalize_key(key)?;
+        Ok(self.entries.lock().unwrap().get(&key).cloned())
+    }
+
+    async fn write(&self, key: &str, data: &[u8]) -> anyhow::Result<()> {
+        let key = normalize_key(key)?;
+        self.entries.lock().unwrap().insert(key, data.to_vec());
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str) -> anyhow::Result<()> {
+        let key = normalize_key(key)?;
+        let prefix = format!("{key}/");
+        self.entries
+            .lock()
+            .unwrap()
+            .retain(|k, _| *k != key && !k.starts_with(&prefix));
+        Ok(())
+    }
+
+    async fn list(&self, dir: &str) -> anyhow::Result<Vec<String>> {
+        let dir = normalize_key(dir)?;
+        let prefix = if dir.is_empty() {
+            String::new()
+        } else {
+            format!("{dir}/")
+        };
+        let mut keys = self
+            .entries
+            .lock()
+            .unwrap()
+            .keys()
+            .filter_map(|k| {
+                let rest = k.strip_prefix(&prefix)?;
+                let child = rest.split('/').next()?;
+                Some(format!("{prefix}{child}"))
+            })
+            .collect::<Vec<_>>();
+        keys.sort();
+        keys.dedup();
+        Ok(keys)
+    }
+}
@@ -1,48 +1,236 @@
+use anyhow::{Context, anyhow};
 use async_trait::async_trait;
-use std::sync::{Arc, Mutex, OnceLock};
+use futures_util::{Stream, StreamExt, stream};
+use std::{
+    any::Any,
+    path::Path,
+    sync::{Arc, OnceLock},
+};
+use tokio::sync::{RwLock, watch};
 
+use crate::{cache::CacheStore, cdn::GitHubCdn};
+
+pub mod download_manager;
+pub mod error;
+pub mod federated;
+pub mod genericjson;
 pub mod legacyparse;
 pub mod models;
+pub mod officialv1;
 pub mod officialv2;
+pub mod singlerepo;
+pub mod validate;
+
+// 读远多于写，用异步读写锁避免在 async 上下文中阻塞执行线程
+pub static COMMUNITY_PROVIDERS: OnceLock<RwLock<Vec<Arc<dyn CommunityProvider>>>> = OnceLock::new();
+
+fn providers() -> &'static RwLock<Vec<Arc<dyn CommunityProvider>>> {
+    COMMUNITY_PROVIDERS.get_or_init(|| RwLock::new(Vec::new()))
+}
 
-pub static COMMUNITY_PROVIDERS: OnceLock<Mutex<Vec<Arc<dyn CommunityProvider>>>> = OnceLock::new();
+// 注册表是进程级全局状态：涉及注册表的测试持有此锁串行执行，并从空注册表开始
+#[cfg(test)]
+pub(crate) async fn isolated_registry() -> tokio::sync::MutexGuard<'static, ()> {
+    static LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+    let guard = LOCK.lock().await;
+    providers().write().await.clear();
+    guard
+}
+
+// 名称在注册表中必须唯一；同名 provider 已存在时拒绝注册，重新初始化请用 replace_community_provider
+pub async fn add_community_provider(provider: Arc<dyn CommunityProvider>) -> anyhow::Result<()> {
+    let name = provider.provider_name();
+    let mut providers = providers().write().await;
+    if providers.iter().any(|p| p.provider_name() == name) {
+        return Err(anyhow!("community provider `{name}` is already registered"));
+    }
+    providers.push(provider);
+    Ok(())
+}
 
-pub async fn add_community_provider(provider: Arc<dyn CommunityProvider>) {
-    let providers = COMMUNITY_PROVIDERS.get_or_init(|| Mutex::new(Vec::new()));
-    let mut locked = providers.lock().unwrap();
-    locked.push(provider);
+// 用同名的新实例替换已注册的 provider，保持其在注册表中的位置；未注册时返回错误
+pub async fn replace_community_provider(
+    provider: Arc<dyn CommunityProvider>,
+) -> anyhow::Result<()> {
+    let name = provider.provider_name();
+    let mut providers = providers().write().await;
+    let slot = providers
+        .iter_mut()
+        .find(|p| p.provider_name() == name)
+        .ok_or_else(|| anyhow!("community provider `{name}` is not registered"))?;
+    *slot = provider;
+    Ok(())
 }
 
 pub async fn remove_community_provider(name: &str) {
-    let providers = COMMUNITY_PROVIDERS.get_or_init(|| Mutex::new(Vec::new()));
-    let mut locked = providers.lock().unwrap();
-    locked.retain(|p| p.provider_name() != name);
+    providers()
+        .write()
+        .await
+        .retain(|p| p.provider_name() != name);
 }
 
 pub async fn get_community_provider(name: &str) -> Option<Arc<dyn CommunityProvider>> {
-    let providers = COMMUNITY_PROVIDERS.get_or_init(|| Mutex::new(Vec::new()));
-    let locked = providers.lock().unwrap();
-    for p in locked.iter() {
-        if p.provider_name() == name {
-            return Some(Arc::clone(p));
+    providers()
+        .read()
+        .await
+        .iter()
+        .find(|p| p.provider_name() == name)
+        .cloned()
+}
+
+// 按名称取出 provider 并还原为具体类型，以调用其专有方法；名称不存在或类型不符时返回 None
+pub async fn get_community_provider_as<T: CommunityProvider + 'static>(
+    name: &str,
+) -> Option<Arc<T>> {
+    get_community_provider(name)
+        .await?
+        .into_any_arc()
+        .downcast::<T>()
+        .ok()
+}
+
+pub async fn get_official_v2_provider() -> Option<Arc<officialv2::OfficialV2Provider>> {
+    get_community_provider_as(officialv2::PROVIDER_NAME).await
+}
+
+// 注册官方 v1 兼容源，store 应与 OfficialV2 的缓存目录分开。
+// 需在 OfficialV2 之后注册并刷新：v1 索引解析与聚合视图都以已注册的 v2 为准去重
+pub async fn add_official_v1_provider(
+    cdn: GitHubCdn,
+    store: Arc<dyn CacheStore>,
+) -> anyhow::Result<Arc<officialv1::OfficialV1Provider>> {
+    let provider = Arc::new(officialv1::OfficialV1Provider::new(cdn, store));
+    add_community_provider(provider.clone()).await?;
+    Ok(provider)
+}
+
+pub async fn get_official_v1_provider() -> Option<Arc<officialv1::OfficialV1Provider>> {
+    get_community_provider_as(officialv1::PROVIDER_NAME).await
+}
+
+// 把所有 provider 的设置以 名称 -> 配置 的 JSON 写入 path（通常位于应用配置目录）
+pub async fn save_all_configs(path: &Path) -> anyhow::Result<()> {
+    let configs = providers()
+        .read()
+        .await
+        .iter()
+        .map(|p| (p.provider_name(), p.export_config()))
+        .filter(|(_, cfg)| !cfg.is_null())
+        .collect::<serde_json::Map<_, _>>();
+    let data =
+        serde_json::to_vec_pretty(&configs).context("failed to serialize provider configs")?;
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent)
+            .await
+            .with_context(|| format!("failed to create directory {}", parent.display()))?;
+    }
+    tokio::fs::write(path, data)
+        .await
+        .with_context(|| format!("failed to write provider configs to {}", path.display()))
+}
+
+// 读取 save_all_configs 写出的文件并应用到已注册的同名 provider；文件不存在视为没有保存过。
+// 未注册的名称忽略，单个 provider 应用失败不影响其他 provider，最后汇总报错
+pub async fn load_all_configs(path: &Path) -> anyhow::Result<()> {
+    let data = match tokio::fs::read(path).await {
+        Ok(data) => data,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => {
+            return Err(anyhow::Error::new(err).context(format!(
+                "failed to read provider configs from {}",
+                path.display()
+            )));
+        }
+    };
+    let configs: serde_json::Map<String, serde_json::Value> = serde_json::from_slice(&data)
+        .with_context(|| format!("invalid provider configs in {}", path.display()))?;
+
+    let mut failures = Vec::new();
+    for (name, cfg) in configs {
+        match get_community_provider(&name).await {
+            Some(provider) => {
+                if let Err(err) = provider.apply_config(cfg) {
+                    failures.push(format!("{name}: {err:#}"));
+                }
+            }
+            None => log::info!("[community] skipped config of unregistered provider `{name}`"),
         }
     }
-    None
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "failed to apply provider configs:\n  - {}",
+            failures.join("\n  - ")
+        ))
+    }
+}
+
+// 把当前已注册 provider 的状态变化合并为一个流，每条带上 provider 名称，便于转发为 Tauri 事件。
+// 订阅时各 provider 先推送一次当前状态；之后注册的 provider 不在流中，需要重新订阅
+pub async fn subscribe_all_states() -> impl Stream<Item = models::common::ProviderStateEvent> {
+    let receivers = providers()
+        .read()
+        .await
+        .iter()
+        .map(|p| (p.provider_name(), p.subscribe_state()))
+        .collect::<Vec<_>>();
+    stream::select_all(receivers.into_iter().map(|(provider, rx)| {
+        stream::unfold((provider, rx), |(provider, mut rx)| async move {
+            rx.changed().await.ok()?;
+            let event = models::common::ProviderStateEvent {
+                provider: provider.clone(),
+                state: rx.borrow_and_update().clone(),
+            };
+            Some((event, (provider, rx)))
+        })
+        .boxed()
+    }))
+}
+
+// 供命令层在调用前判断操作是否可用；provider 未注册时为 None
+pub async fn get_community_provider_capabilities(
+    name: &str,
+) -> Option<models::common::ProviderCapabilities> {
+    Some(get_community_provider(name).await?.capabilities())
 }
 
 pub async fn list_community_providers() -> Vec<String> {
-    let providers = COMMUNITY_PROVIDERS.get_or_init(|| Mutex::new(Vec::new()));
-    let locked = providers.lock().unwrap();
-    locked.iter().map(|p| p.provider_name()).collect()
+    providers()
+        .read()
+        .await
+        .iter()
+        .map(|p| p.provider_name())
+        .collect()
 }
 
 #[async_trait]
 pub trait CommunityProvider: Send + Sync {
     fn provider_name(&self) -> String;
+
+    // 供调用方向下转型到具体 provider；实现均为返回 self
+    fn as_any(&self) -> &dyn Any;
+    fn into_any_arc(self: Arc<Self>) -> Arc<dyn Any + Send + Sync>;
+
+    // cfg 为 JSON 字符串形式的 provider 配置（各 provider 自行定义字段），空字符串表示使用默认配置；
+    // 配置无法解析时返回错误
     async fn refresh(&self, cfg: &str) -> anyhow::Result<()>;
 
     fn state(&self) -> models::common::ProviderState;
 
+    // 默认视为支持全部操作
+    fn capabilities(&self) -> models::common::ProviderCapabilities {
+        models::common::ProviderCapabilities::ALL
+    }
+
+    // 订阅状态变化（Updating / Ready / Failed 等），替代轮询 state()。
+    // 新订阅者的首次 changed() 立即返回当前状态；默认实现只推送这一次，之后不会再变化
+    fn subscribe_state(&self) -> watch::Receiver<models::common::ProviderState> {
+        let mut rx = watch::channel(self.state()).1;
+        rx.mark_changed();
+        rx
+    }
+
     async fn get_page(
         &self,
         page: u32,
@@ -62,6 +250,28 @@ pub trait CommunityProvider: Send + Sync {
     ) -> anyhow::Result<std::path::PathBuf>;
     async fn get_total_items(&self) -> anyhow::Result<u64>;
 
+    // 按 id 批量取列表项，保持输入顺序，未知 id 为 None
+    async fn get_items_by_ids(
+        &self,
+        ids: &[String],
+    ) -> anyhow::Result<Vec<Option<models::common::ManifestItemV2>>> {
+        let mut items = Vec::with_capacity(ids.len());
+        for id in ids {
+            items.push(
+                self.get_item_manifest(id.clone())
+                    .await
+                    .ok()
+                    .map(|m| m.item),
+            );
+        }
+        Ok(items)
+    }
+
+    // 搜索框自动补全，不支持的 provider 返回空列表
+    async fn suggest(&self, _prefix: &str, _limit: usize) -> anyhow::Result<Vec<String>> {
+        Ok(Vec::new())
+    }
+
     async fn probe_download_size(
         &self,
         _item_id: String,
@@ -69,4 +279,108 @@ pub trait CommunityProvider: Send + Sync {
     ) -> anyhow::Result<Option<u64>> {
         Ok(None)
     }
+
+    // 可持久化的设置（CDN、自定义仓库等），无设置的 provider 返回 Null
+    fn export_config(&self) -> serde_json::Value {
+        serde_json::Value::Null
+    }
+
+    // 应用 export_config 导出的设置，只改内存状态、不触发刷新；格式不符时返回错误
+    fn apply_config(&self, _cfg: serde_json::Value) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::MemoryCacheStore;
+
+    #[tokio::test]
+    async fn official_v2_is_retrieved_with_its_concrete_type() {
+        let _registry = isolated_registry().await;
+        let provider = Arc::new(officialv2::OfficialV2Provider::new(
+            GitHubCdn::Raw,
+            Arc::new(MemoryCacheStore::new()),
+        ));
+        add_community_provider(provider.clone()).await.unwrap();
+
+        let typed = get_official_v2_provider().await.unwrap();
+        assert!(Arc::ptr_eq(&typed, &provider));
+        assert!(
+            get_community_provider_as::<genericjson::GenericJsonProvider>(
+                officialv2::PROVIDER_NAME
+            )
+            .await
+            .is_none()
+        );
+
+        remove_community_provider(officialv2::PROVIDER_NAME).await;
+        assert!(get_official_v2_provider().await.is_none());
+    }
+
+    fn generic(name: &str, base_url: &str) -> Arc<genericjson::GenericJsonProvider> {
+        Arc::new(
+            genericjson::GenericJsonProvider::new(
+                name,
+                base_url,
+                "index.json",
+                Arc::new(MemoryCacheStore::new()),
+            )
+            .unwrap(),
+        )
+    }
+
+    async fn registered_names() -> Vec<String> {
+        providers()
+            .read()
+            .await
+            .iter()
+            .map(|p| p.provider_name())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn adding_a_duplicate_name_is_rejected() {
+        let _registry = isolated_registry().await;
+        add_community_provider(generic("A", "https://a.example"))
+            .await
+            .unwrap();
+        let err = add_community_provider(generic("A", "https://other.example"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("already registered"));
+        assert_eq!(registered_names().await, ["A"]);
+    }
+
+    #[tokio::test]
+    async fn replace_keeps_registry_position() {
+        let _registry = isolated_registry().await;
+        add_community_provider(generic("A", "https://a.example"))
+            .await
+            .unwrap();
+        add_community_provider(generic("B", "https://b.example"))
+            .await
+            .unwrap();
+        let replacement = generic("A", "https://new.example");
+        replace_community_provider(replacement.clone())
+            .await
+            .unwrap();
+
+        assert_eq!(registered_names().await, ["A", "B"]);
+        let current = get_community_provider_as::<genericjson::GenericJsonProvider>("A")
+            .await
+            .unwrap();
+        assert!(Arc::ptr_eq(&current, &replacement));
+    }
+
+    #[tokio::test]
+    async fn replacing_a_missing_provider_fails() {
+        let _registry = isolated_registry().await;
+        let err = replace_community_provider(generic("Missing", "https://m.example"))
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("not registered"));
+        assert!(registered_names().await.is_empty());
+    }
 }
@@ -0,0 +1,344 @@
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicU64, Ordering},
+    },
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::{Semaphore, broadcast};
+
+use crate::community::{CommunityProvider, models::common::ProgressData};
+
+const EVENT_CHANNEL_CAPACITY: usize = 256;
+
+pub type DownloadId = u64;
+
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub enum DownloadStatus {
+    Queued,
+    Running { progress: f32 },
+    Done(PathBuf),
+    Failed(String),
+    Cancelled,
+}
+
+impl DownloadStatus {
+    pub fn is_finished(&self) -> bool {
+        matches!(
+            self,
+            DownloadStatus::Done(_) | DownloadStatus::Failed(_) | DownloadStatus::Cancelled
+        )
+    }
+}
+
+// 每次状态变化（含进度）广播一次
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DownloadEvent {
+    pub id: DownloadId,
+    pub item_id: String,
+    pub device: String,
+    pub status: DownloadStatus,
+}
+
+struct DownloadJob {
+    item_id: String,
+    device: String,
+    status: DownloadStatus,
+    handle: Option<tauri::async_runtime::JoinHandle<()>>,
+}
+
+struct Shared {
+    jobs: Mutex<HashMap<DownloadId, DownloadJob>>,
+    events: broadcast::Sender<DownloadEvent>,
+}
+
+impl Shared {
+    // 已结束（尤其是已取消）的任务不再接受状态更新
+    fn update(&self, id: DownloadId, status: DownloadStatus) {
+        let mut jobs = self.jobs.lock().unwrap();
+        let Some(job) = jobs.get_mut(&id) else {
+            return;
+        };
+        if job.status.is_finished() {
+            return;
+        }
+        job.status = status.clone();
+        let _ = self.events.send(DownloadEvent {
+            id,
+            item_id: job.item_id.clone(),
+            device: job.device.clone(),
+            status,
+        });
+    }
+}
+
+// 下载队列：按并发上限依次执行 provider.download，并跟踪每个任务的状态
+pub struct DownloadManager {
+    provider: Arc<dyn CommunityProvider>,
+    permits: Arc<Semaphore>,
+    next_id: AtomicU64,
+    shared: Arc<Shared>,
+}
+
+impl DownloadManager {
+    pub fn new(provider: Arc<dyn CommunityProvider>, concurrency: usize) -> Self {
+        let (events, _) = broadcast::channel(EVENT_CHANNEL_CAPACITY);
+        Self {
+            provider,
+            permits: Arc::new(Semaphore::new(concurrency.max(1))),
+            next_id: AtomicU64::new(1),
+            shared: Arc::new(Shared {
+                jobs: Mutex::new(HashMap::new()),
+                events,
+            }),
+        }
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<DownloadEvent> {
+        self.shared.events.subscribe()
+    }
+
+    pub fn enqueue(&self, item_id: String, device: String) -> DownloadId {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        self.shared.jobs.lock().unwrap().insert(
+            id,
+            DownloadJob {
+                item_id: item_id.clone(),
+                device: device.clone(),
+                status: DownloadStatus::Queued,
+                handle: None,
+            },
+        );
+        let _ = self.shared.events.send(DownloadEvent {
+            id,
+            item_id: item_id.clone(),
+            device: device.clone(),
+            status: DownloadStatus::Queued,
+        });
+
+        let provider = Arc::clone(&self.provider);
+        let permits = Arc::clone(&self.permits);
+        let shared = Arc::clone(&self.shared);
+        let handle = tauri::async_runtime::spawn(async move {
+            let Ok(_permit) = permits.acquire_owned().await else {
+                return;
+            };
+            shared.update(id, DownloadStatus::Running { progress: 0.0 });
+
+            let progress_shared = Arc::clone(&shared);
+            let progress_cb = Box::new(move |data: ProgressData| {
+                progress_shared.update(
+                    id,
+                    DownloadStatus::Running {
+                        progress: data.progress,
+                    },
+                );
+            });
+            let status = match provider.download(item_id, device, Some(progress_cb)).await {
+                Ok(path) => DownloadStatus::Done(path),
+                Err(err) => DownloadStatus::Failed(format!("{err:#}")),
+            };
+            shared.update(id, status);
+        });
+
+        // 任务可能已经结束，只在记录仍在时挂上句柄
+        if let Some(job) = self.shared.jobs.lock().unwrap().get_mut(&id)
+            && !job.status.is_finished()
+        {
+            job.handle = Some(handle);
+        }
+        id
+    }
+
+    pub fn status(&self, id: DownloadId) -> Option<DownloadStatus> {
+        self.shared
+            .jobs
+            .lock()
+            .unwrap()
+            .get(&id)
+            .map(|job| job.status.clone())
+    }
+
+    // 全部任务及状态，按入队顺序
+    pub fn list(&self) -> Vec<(DownloadId, DownloadStatus)> {
+        let jobs = self.shared.jobs.lock().unwrap();
+        let mut list = jobs
+            .iter()
+            .map(|(id, job)| (*id, job.status.clone()))
+            .collect::<Vec<_>>();
+        list.sort_by_key(|(id, _)| *id);
+        list
+    }
+
+    // 取消排队中或进行中的任务；已结束的任务返回 false
+    pub fn cancel(&self, id: DownloadId) -> bool {
+        let handle = {
+            let mut jobs = self.shared.jobs.lock().unwrap();
+            let Some(job) = jobs.get_mut(&id) else {
+                return false;
+            };
+            if job.status.is_finished() {
+                return false;
+            }
+            job.handle.take()
+        };
+        self.shared.update(id, DownloadStatus::Cancelled);
+        if let Some(handle) = handle {
+            handle.abort();
+        }
+        true
+    }
+
+    // 清除已结束的任务记录
+    pub fn clear_finished(&self) {
+        self.shared
+            .jobs
+            .lock()
+            .unwrap()
+            .retain(|_, job| !job.status.is_finished());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use axum::{
+        Router,
+        body::{Body, Bytes},
+        routing::get,
+    };
+    use futures_util::StreamExt;
+
+    use super::*;
+    use crate::test_support::{StubDownload, StubProvider, serve};
+
+    // 收集事件直到 count 个任务结束
+    async fn collect_until_finished(
+        events: &mut broadcast::Receiver<DownloadEvent>,
+        count: usize,
+    ) -> Vec<DownloadEvent> {
+        let mut collected = Vec::new();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while collected
+                .iter()
+                .filter(|event: &&DownloadEvent| event.status.is_finished())
+                .count()
+                < count
+            {
+                collected.push(events.recv().await.unwrap());
+            }
+        })
+        .await
+        .expect("downloads did not finish");
+        collected
+    }
+
+    fn statuses(events: &[DownloadEvent]) -> Vec<DownloadStatus> {
+        events.iter().map(|event| event.status.clone()).collect()
+    }
+
+    #[tokio::test]
+    async fn events_follow_queued_running_done() {
+        let provider = StubProvider::new("stub", StubDownload::Sleep(Duration::ZERO));
+        let manager = DownloadManager::new(provider, 1);
+        let mut events = manager.subscribe();
+        let id = manager.enqueue("w1".into(), "o66".into());
+
+        let events = collect_until_finished(&mut events, 1).await;
+        assert!(events.iter().all(|event| event.id == id));
+        assert_eq!(
+            statuses(&events),
+            [
+                DownloadStatus::Queued,
+                DownloadStatus::Running { progress: 0.0 },
+                DownloadStatus::Done(PathBuf::from("w1")),
+            ]
+        );
+        assert_eq!(manager.status(id), Some(DownloadStatus::Done("w1".into())));
+    }
+
+    #[tokio::test]
+    async fn events_follow_queued_running_failed() {
+        let manager = DownloadManager::new(StubProvider::new("stub", StubDownload::Fail), 1);
+        let mut events = manager.subscribe();
+        manager.enqueue("w1".into(), "o66".into());
+
+        assert_eq!(
+            statuses(&collect_until_finished(&mut events, 1).await),
+            [
+                DownloadStatus::Queued,
+                DownloadStatus::Running { progress: 0.0 },
+                DownloadStatus::Failed("boom".into()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn concurrency_limit_is_respected() {
+        let provider = StubProvider::new("stub", StubDownload::Sleep(Duration::from_millis(20)));
+        let manager = DownloadManager::new(provider.clone(), 2);
+        let mut events = manager.subscribe();
+        for i in 0..5 {
+            manager.enqueue(format!("w{i}"), "o66".into());
+        }
+
+        collect_until_finished(&mut events, 5).await;
+        assert_eq!(provider.peak.load(Ordering::SeqCst), 2);
+        assert!(
+            manager
+                .list()
+                .iter()
+                .all(|(_, status)| matches!(status, DownloadStatus::Done(_)))
+        );
+    }
+
+    // 服务端发出首块后不再发送，下载停在写临时文件的中途
+    #[tokio::test]
+    async fn cancelling_a_running_download_leaves_no_files() {
+        let router =
+            Router::new().route(
+                "/stall.bin",
+                get(|| async {
+                    let head = futures_util::stream::iter([Ok::<_, std::io::Error>(Bytes::from(
+                        vec![0u8; 1024],
+                    ))]);
+                    Body::from_stream(head.chain(futures_util::stream::pending()))
+                }),
+            );
+        let base = serve(router).await;
+        let dir = tempfile::tempdir().unwrap();
+        let provider = StubProvider::new(
+            "stub",
+            StubDownload::File {
+                url: format!("{base}/stall.bin"),
+                dir: dir.path().to_path_buf(),
+            },
+        );
+        let manager = DownloadManager::new(provider, 1);
+        let id = manager.enqueue("w1".into(), "o66".into());
+
+        let entries = || std::fs::read_dir(dir.path()).unwrap().count();
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while entries() == 0 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("temp file was not created");
+
+        assert!(manager.cancel(id));
+        assert_eq!(manager.status(id), Some(DownloadStatus::Cancelled));
+        assert!(!manager.cancel(id));
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while entries() > 0 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .expect("temp file was left behind");
+    }
+}
@@ -0,0 +1,99 @@
+use std::{fmt, time::Duration};
+
+// 需要调用方区分处理的错误，经 anyhow::Error::downcast_ref 识别
+#[derive(Debug, Clone, PartialEq)]
+pub enum ProviderError {
+    Offline,                  // 离线模式下拒绝联网
+    NoArtifacts(String),      // manifest 中没有任何可下载产物（资源 id）
+    Incompatible(String),     // 严格模式下产物与目标设备不兼容（原因）
+    NotFound(String),         // 资源仓库或 manifest 已不存在（owner/repo@commit）
+    Network(String),          // 网络问题，稍后重试可能恢复（owner/repo@commit）
+    EmptyIndex(usize),        // 索引解析成功但没有可用条目（被跳过的无效行数）
+    ChecksumMismatch(String), // 开启校验时，下载内容与仓库发布的 sha256 不一致（文件名）
+    ManifestTooLarge(usize),  // manifest 超过大小上限（上限字节数）
+    Timeout(String),          // 请求在限定时间内未完成（URL）
+    Cooldown(Duration),       // 冷却期内以相同配置重复 refresh，未联网（剩余时间）
+}
+
+impl fmt::Display for ProviderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProviderError::Offline => write!(f, "离线模式下无法访问网络"),
+            ProviderError::NoArtifacts(id) => write!(f, "资源 {id} 没有可下载的文件"),
+            ProviderError::Incompatible(reason) => write!(f, "资源与设备不兼容: {reason}"),
+            ProviderError::NotFound(repo) => write!(f, "资源仓库 {repo} 不存在或已删除"),
+            ProviderError::Network(repo) => write!(f, "获取 {repo} 时网络错误，请稍后重试"),
+            ProviderError::EmptyIndex(0) => write!(f, "empty index: 资源索引为空"),
+            ProviderError::EmptyIndex(skipped) => {
+                write!(f, "empty index: 资源索引没有有效条目（跳过 {skipped} 行）")
+            }
+            ProviderError::ChecksumMismatch(file) => {
+                write!(f, "{file} 校验失败，当前下载源的内容可能已被篡改")
+            }
+            ProviderError::ManifestTooLarge(limit) => {
+                write!(f, "manifest 超过大小上限（{limit} 字节）")
+            }
+            ProviderError::Timeout(url) => write!(f, "请求 {url} 超时"),
+            ProviderError::Cooldown(remaining) => {
+                write!(
+                    f,
+                    "刷新过于频繁，请 {} 秒后再试",
+                    remaining.as_secs().max(1)
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ProviderError {}
+
+// manifest 校验失败，列出全部问题，便于仓库作者一次性修正
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManifestValidationError {
+    pub item_id: String,
+    pub violations: Vec<ManifestViolation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManifestViolation {
+    pub field: String, // 字段路径，如 `downloads.default.version`
+    pub message: String,
+}
+
+impl fmt::Display for ManifestValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "manifest of `{}` is invalid:", self.item_id)?;
+        for violation in &self.violations {
+            write!(f, "\n  - {}: {}", violation.field, violation.message)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ManifestValidationError {}
+
+// 下载时所有镜像均失败，按尝试顺序保留每个镜像的原始错误
+#[derive(Debug)]
+pub struct MirrorFailures {
+    pub file_name: String,
+    pub failures: Vec<MirrorFailure>,
+}
+
+#[derive(Debug)]
+pub struct MirrorFailure {
+    pub mirror: String, // 镜像名，当前 CDN 为 `current`
+    pub url: String,
+    pub error: anyhow::Error,
+}
+
+impl fmt::Display for MirrorFailures {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "all mirrors failed for {}:", self.file_name)?;
+        for failure in &self.failures {
+            write!(f, "\n  - {}: {:#}", failure.mirror, failure.error)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for MirrorFailures {}
@@ -0,0 +1,201 @@
+// 跨 provider 的聚合视图：并发查询所有已注册 provider，按注册顺序轮流交错合并结果。
+// 聚合结果中的资源以 `provider:item_id` 形式的限定 id 标识，详情与下载据此路由
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::anyhow;
+use futures_util::future::join_all;
+
+use crate::community::{
+    CommunityProvider, get_community_provider, get_official_v2_provider,
+    models::common::{
+        FederatedPage, ManifestV2, ProgressData, ProviderFailure, SearchConfig, SourcedItem,
+    },
+    officialv1, providers,
+};
+
+pub fn qualify_id(provider: &str, item_id: &str) -> String {
+    format!("{provider}:{item_id}")
+}
+
+// 只在第一个 `:` 处切分，资源 id 本身可以包含 `:`
+pub fn split_qualified_id(qualified: &str) -> Option<(&str, &str)> {
+    qualified
+        .split_once(':')
+        .filter(|(provider, item_id)| !provider.is_empty() && !item_id.is_empty())
+}
+
+// 单个 provider 失败只记入 errors，不影响其他 provider 的结果。
+// 有关键词时跳过不支持搜索的 provider（否则会混入未过滤的结果），不分页的 provider 只参与第 0 页
+pub async fn get_page_all(page: u32, limit: u32, search: SearchConfig) -> FederatedPage {
+    let has_keyword = search
+        .filter
+        .as_deref()
+        .is_some_and(|filter| !filter.trim().is_empty());
+    let snapshot = providers()
+        .read()
+        .await
+        .iter()
+        .filter(|provider| {
+            let caps = provider.capabilities();
+            (caps.supports_search || !has_keyword) && (caps.paginated || page == 0)
+        })
+        .cloned()
+        .collect::<Vec<_>>();
+    let results = join_all(snapshot.iter().map(|provider| {
+        let search = search.clone();
+        async move {
+            (
+                provider.provider_name(),
+                provider.get_page(page, limit, search).await,
+            )
+        }
+    }))
+    .await;
+
+    // v1 索引加载后 v2 才刷新时两边可能有同一资源，以 v2 为准。
+    // 在合并前按 v2 索引剔除，而不是只比对本页，避免 v1 列中留下空位打乱交错顺序
+    let official_v2 = get_official_v2_provider().await;
+    let mut errors = Vec::new();
+    let mut columns = Vec::new();
+    for (provider, result) in results {
+        match result {
+            Ok(mut items) => {
+                if provider == officialv1::PROVIDER_NAME
+                    && let Some(v2) = &official_v2
+                {
+                    items.retain(|item| !v2.has_item(&item.id));
+                }
+                columns.push((provider, items.into_iter()))
+            }
+            Err(err) => errors.push(ProviderFailure {
+                provider,
+                error: format!("{err:#}"),
+            }),
+        }
+    }
+
+    // 轮流从每个 provider 取一项，结果与各 provider 的返回顺序一一对应，保证确定性
+    let mut items = Vec::new();
+    loop {
+        let mut progressed = false;
+        for (provider, column) in columns.iter_mut() {
+            if let Some(item) = column.next() {
+                items.push(SourcedItem {
+                    provider: provider.clone(),
+                    item,
+                });
+                progressed = true;
+            }
+        }
+        if !progressed {
+            break;
+        }
+    }
+
+    FederatedPage { items, errors }
+}
+
+async fn resolve_qualified(
+    qualified: &str,
+) -> anyhow::Result<(Arc<dyn CommunityProvider>, String)> {
+    let (provider_name, item_id) = split_qualified_id(qualified)
+        .ok_or_else(|| anyhow!("`{qualified}` is not a provider-qualified id"))?;
+    let provider = get_community_provider(provider_name)
+        .await
+        .ok_or_else(|| anyhow!("provider `{provider_name}` is not registered"))?;
+    Ok((provider, item_id.to_string()))
+}
+
+pub async fn get_item_manifest_qualified(qualified: &str) -> anyhow::Result<ManifestV2> {
+    let (provider, item_id) = resolve_qualified(qualified).await?;
+    provider.get_item_manifest(item_id).await
+}
+
+pub async fn download_qualified(
+    qualified: &str,
+    device: String,
+    progress_cb: Option<Box<dyn Fn(ProgressData) + Send>>,
+) -> anyhow::Result<PathBuf> {
+    let (provider, item_id) = resolve_qualified(qualified).await?;
+    if !provider.capabilities().supports_download {
+        return Err(anyhow!(
+            "provider `{}` does not support downloads",
+            provider.provider_name()
+        ));
+    }
+    provider.download(item_id, device, progress_cb).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        cache::MemoryCacheStore,
+        cdn::GitHubCdn,
+        community::{
+            add_community_provider, isolated_registry, models::common::SortRuleV2,
+            officialv1::OfficialV1Provider, officialv2::OfficialV2Provider,
+        },
+        test_support::{StubProvider, index_item},
+    };
+
+    fn by_name() -> SearchConfig {
+        SearchConfig {
+            sort: SortRuleV2::Name,
+            ..Default::default()
+        }
+    }
+
+    // v2: a b；v1: a c d（a 已迁移到 v2）；另有一个总是失败的 provider
+    async fn register_providers() {
+        let v2 = OfficialV2Provider::new(GitHubCdn::Raw, Arc::new(MemoryCacheStore::new()));
+        v2.load_index(vec![index_item("a", "a", &[]), index_item("b", "b", &[])]);
+        let v1 = OfficialV1Provider::new(GitHubCdn::Raw, Arc::new(MemoryCacheStore::new()));
+        v1.inner().load_index(vec![
+            index_item("a", "a", &[]),
+            index_item("c", "c", &[]),
+            index_item("d", "d", &[]),
+        ]);
+        add_community_provider(Arc::new(v2)).await.unwrap();
+        add_community_provider(Arc::new(v1)).await.unwrap();
+        add_community_provider(StubProvider::failing("Failing"))
+            .await
+            .unwrap();
+    }
+
+    fn sourced_ids(page: &FederatedPage) -> Vec<String> {
+        page.items
+            .iter()
+            .map(|sourced| qualify_id(&sourced.provider, &sourced.item.id))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn v1_duplicates_are_dropped_before_interleaving() {
+        let _registry = isolated_registry().await;
+        register_providers().await;
+
+        let page = get_page_all(0, 10, by_name()).await;
+        assert_eq!(
+            sourced_ids(&page),
+            [
+                "OfficialV2:a",
+                "OfficialV1:c",
+                "OfficialV2:b",
+                "OfficialV1:d"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn failing_provider_is_reported_without_dropping_others() {
+        let _registry = isolated_registry().await;
+        register_providers().await;
+
+        let page = get_page_all(0, 10, by_name()).await;
+        assert_eq!(page.items.len(), 4);
+        assert_eq!(page.errors.len(), 1);
+        assert_eq!(page.errors[0].provider, "Failing");
+        assert!(page.errors[0].error.contains("unavailable"));
+    }
+}
@@ -0,0 +1,516 @@
+// 通用 JSON 索引 provider：索引为 `<base_url>/<index_path>` 处的 JSON 数组，
+// 每项为 ManifestItemV2 字段，另可内嵌完整 manifest，或给出 manifest / 产物地址。
+// 相对地址均相对 base_url 解析
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+use anyhow::{Context, anyhow};
+use arc_swap::ArcSwap;
+use async_trait::async_trait;
+use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
+
+use crate::{
+    cache::CacheStore,
+    community::{
+        CommunityProvider,
+        error::ProviderError,
+        models::common::{
+            KeywordMode, ManifestDownloadV2, ManifestItemV2, ManifestV2, PaidTypeV2, ProgressData,
+            ProviderCapabilities, ProviderState, ResourceTypeV2, SearchConfig, SortOrder,
+            SortRuleV2,
+        },
+        officialv2::{HIDE_FORCE_PAID, HIDE_PAID, QUICK_APP, WATCHFACE, split_search_terms},
+        validate::validate_manifest,
+    },
+    download::{self, DownloadOptions, sanitize_local_filename},
+};
+
+// 索引中的一项
+#[derive(Debug, Clone, Deserialize)]
+pub struct GenericIndexEntry {
+    #[serde(flatten)]
+    pub item: ManifestItemV2,
+    // 内嵌的完整 manifest，优先于 manifest_url
+    #[serde(default)]
+    pub manifest: Option<ManifestV2>,
+    #[serde(default, rename = "manifestUrl", alias = "manifest_url")]
+    pub manifest_url: Option<String>,
+    // 既无 manifest 也无 manifest_url 时直接使用的产物表（键为设备 id 或 `default`）
+    #[serde(default)]
+    pub downloads: HashMap<String, ManifestDownloadV2>,
+}
+
+// refresh 的 cfg，字段缺省时沿用构造时的地址；同时是 export_config / apply_config 的格式
+#[derive(Debug, Default, Deserialize, Serialize)]
+struct GenericRefreshConfig {
+    #[serde(default)]
+    base_url: Option<String>,
+    #[serde(default)]
+    index_path: Option<String>,
+}
+
+pub struct GenericJsonProvider {
+    name: String,
+    base_url: ArcSwap<String>,
+    index_path: ArcSwap<String>,
+    store: Arc<dyn CacheStore>,
+    entries: ArcSwap<Vec<GenericIndexEntry>>,
+    state: watch::Sender<ProviderState>,
+    session_seed: u64, // 随机排序种子，SearchConfig 未指定时使用
+}
+
+impl GenericJsonProvider {
+    // name 作为 provider 名称，不能含 `:`（聚合视图用它拼接限定 id）
+    pub fn new(
+        name: &str,
+        base_url: &str,
+        index_path: &str,
+        store: Arc<dyn CacheStore>,
+    ) -> anyhow::Result<Self> {
+        let name = name.trim();
+        if name.is_empty() || name.contains(':') {
+            return Err(anyhow!("invalid provider name `{name}`"));
+        }
+        Ok(Self {
+            name: name.to_string(),
+            base_url: ArcSwap::new(Arc::new(base_url.trim().to_string())),
+            index_path: ArcSwap::new(Arc::new(index_path.trim().to_string())),
+            store,
+            entries: ArcSwap::new(Arc::new(Vec::new())),
+            state: watch::Sender::new(ProviderState::Updating),
+            session_seed: rand::random(),
+        })
+    }
+
+    pub fn base_url(&self) -> String {
+        self.base_url.load().to_string()
+    }
+
+    pub fn index_path(&self) -> String {
+        self.index_path.load().to_string()
+    }
+
+    fn apply_urls(&self, cfg: GenericRefreshConfig) {
+        if let Some(base_url) = cfg.base_url {
+            self.base_url.store(Arc::new(base_url.trim().to_string()));
+        }
+        if let Some(index_path) = cfg.index_path {
+            self.index_path
+                .store(Arc::new(index_path.trim().to_string()));
+        }
+    }
+
+    // 绝对地址（含 data:）原样返回，其余拼接到 base_url 之后
+    fn resolve_url(&self, path: &str) -> String {
+        let path = path.trim();
+        if path.is_empty()
+            || path.starts_with("http://")
+            || path.starts_with("https://")
+            || path.starts_with("data:")
+        {
+            return path.to_string();
+        }
+        let path = path.trim_start_matches("./").trim_start_matches('/');
+        format!("{}/{}", self.base_url.load().trim_end_matches('/'), path)
+    }
+
+    fn resolve_item_urls(&self, item: &mut ManifestItemV2) {
+        item.icon = self.resolve_url(&item.icon);
+        item.cover = self.resolve_url(&item.cover);
+        for preview in item.preview.iter_mut() {
+            *preview = self.resolve_url(preview);
+        }
+        for media in item.preview_media.iter_mut() {
+            media.url = self.resolve_url(&media.url);
+        }
+    }
+
+    async fn fetch_json<T: serde::de::DeserializeOwned>(&self, url: &str) -> anyhow::Result<T> {
+        let body = crate::net::default_client()
+            .get(url)
+            .send()
+            .await
+            .with_context(|| format!("failed to request {url}"))?
+            .error_for_status()
+            .with_context(|| format!("request returned error for {url}"))?
+            .bytes()
+            .await
+            .with_context(|| format!("failed to read {url}"))?;
+        serde_json::from_slice(&body).with_context(|| format!("failed to parse {url}"))
+    }
+
+    async fn refresh_inner(&self, cfg: &str) -> anyhow::Result<()> {
+        let cfg = if cfg.trim().is_empty() {
+            GenericRefreshConfig::default()
+        } else {
+            serde_json::from_str::<GenericRefreshConfig>(cfg)
+                .with_context(|| format!("invalid refresh config: {cfg}"))?
+        };
+        self.apply_urls(cfg);
+
+        let index_url = self.resolve_url(&self.index_path.load());
+        let mut entries = self
+            .fetch_json::<Vec<GenericIndexEntry>>(&index_url)
+            .await
+            .context("索引加载失败")?;
+        entries.retain(|entry| !entry.item.id.trim().is_empty());
+        if entries.is_empty() {
+            return Err(ProviderError::EmptyIndex(0).into());
+        }
+        for entry in entries.iter_mut() {
+            self.resolve_item_urls(&mut entry.item);
+        }
+        self.entries.store(Arc::new(entries));
+        Ok(())
+    }
+
+    fn entry(&self, item_id: &str) -> Option<GenericIndexEntry> {
+        self.entries
+            .load()
+            .iter()
+            .find(|entry| entry.item.id == item_id)
+            .cloned()
+    }
+
+    // 按搜索条件过滤并排序；索引没有设备表，chip 与设备分类不生效
+    fn filter_items(&self, search: &SearchConfig) -> Vec<ManifestItemV2> {
+        let mut items = self
+            .entries
+            .load()
+            .iter()
+            .map(|entry| entry.item.clone())
+            .collect::<Vec<_>>();
+
+        if let Some(categories) = &search.category {
+            let hide_paid = categories.iter().any(|c| c == HIDE_PAID);
+            let hide_force_paid = categories.iter().any(|c| c == HIDE_FORCE_PAID);
+            let quick_app = categories.iter().any(|c| c == QUICK_APP);
+            let watchface = categories.iter().any(|c| c == WATCHFACE);
+            let res_type = match (quick_app, watchface) {
+                (true, false) => Some(ResourceTypeV2::QuickApp),
+                (false, true) => Some(ResourceTypeV2::WatchFace),
+                _ => None,
+            };
+            items.retain(|item| {
+                let hidden = match item.paid_type {
+                    Some(PaidTypeV2::ForcePaid) => hide_force_paid,
+                    Some(PaidTypeV2::Paid) => hide_paid,
+                    _ => false,
+                };
+                !hidden && res_type.as_ref().is_none_or(|t| &item.restype == t)
+            });
+        }
+
+        let terms = search
+            .filter
+            .as_deref()
+            .map(split_search_terms)
+            .unwrap_or_default();
+        if !terms.is_empty() {
+            let matches = |item: &ManifestItemV2, term: &String| {
+                item.name.to_lowercase().contains(term.as_str())
+                    || item.description.to_lowercase().contains(term.as_str())
+                    || item
+                        .tags
+                        .iter()
+                        .any(|tag| tag.to_lowercase().contains(term.as_str()))
+            };
+            items.retain(|item| match search.keyword_mode {
+                KeywordMode::Any => terms.iter().any(|term| matches(item, term)),
+                KeywordMode::All => terms.iter().all(|term| matches(item, term)),
+            });
+        }
+
+        if let Some(restypes) = search.exclude_restype.as_ref().filter(|r| !r.is_empty()) {
+            items.retain(|item| !restypes.contains(&item.restype));
+        }
+        if let Some(exclude_tags) = &search.exclude_tags {
+            let exclude_tags = exclude_tags
+                .iter()
+                .map(|tag| tag.trim().to_lowercase())
+                .filter(|tag| !tag.is_empty())
+                .collect::<Vec<_>>();
+            if !exclude_tags.is_empty() {
+                items.retain(|item| {
+                    !item
+                        .tags
+                        .iter()
+                        .any(|tag| exclude_tags.contains(&tag.trim().to_lowercase()))
+                });
+            }
+        }
+
+        let order = search.order.unwrap_or(search.sort.default_order());
+        match search.sort {
+            SortRuleV2::Random => {
+                let seed = search.seed.unwrap_or(self.session_seed);
+                items.shuffle(&mut StdRng::seed_from_u64(seed));
+                return items;
+            }
+            SortRuleV2::Name => items.sort_by(|a, b| {
+                a.name
+                    .to_lowercase()
+                    .cmp(&b.name.to_lowercase())
+                    .then_with(|| a.name.cmp(&b.name))
+            }),
+            SortRuleV2::Time => {} // 按索引文档中的顺序（旧→新）
+        }
+        if order == SortOrder::Desc {
+            items.reverse();
+        }
+        items
+    }
+
+    async fn download_inner(
+        &self,
+        item_id: String,
+        device: String,
+        progress_cb: Option<Box<dyn Fn(ProgressData) + Send>>,
+    ) -> anyhow::Result<PathBuf> {
+        let manifest = self.get_item_manifest(item_id.clone()).await?;
+        let entry = manifest
+            .downloads
+            .get(&device)
+            .or_else(|| manifest.downloads.get("default"))
+            .cloned()
+            .ok_or_else(|| ProviderError::NoArtifacts(item_id.clone()))?;
+        // 没有 url 时 file_name 视为相对 base_url 的路径
+        let url = entry
+            .url
+            .as_deref()
+            .filter(|url| !url.trim().is_empty())
+            .unwrap_or(&entry.file_name);
+        let url = Some(self.resolve_url(url))
+            .filter(|url| !url.is_empty())
+            .ok_or_else(|| anyhow!("no download url for {}", entry.file_name))?;
+
+        // 以 provider 名称分隔，多个实例共用同一缓存目录时互不覆盖
+        let key = format!(
+            "{}/{}/{}",
+            self.name,
+            item_id,
+            sanitize_local_filename(&entry.file_name)
+        );
+        let final_path = self
+            .store
+            .local_path(&key)
+            .ok_or_else(|| anyhow!("cache store does not provide local files"))?;
+
+        let opts = DownloadOptions {
+            file_name: Some(entry.file_name.clone()),
+            expected_sha256: entry.sha256.clone(),
+            ..Default::default()
+        };
+        let downloaded = download::download_to_file(
+            &crate::net::default_client(),
+            &url,
+            &final_path,
+            opts,
+            progress_cb,
+        )
+        .await?;
+        Ok(downloaded.path)
+    }
+}
+
+#[async_trait]
+impl CommunityProvider for GenericJsonProvider {
+    fn provider_name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn into_any_arc(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
+        self
+    }
+
+    // cfg 可选 `base_url` / `index_path`，覆盖构造时的地址
+    async fn refresh(&self, cfg: &str) -> anyhow::Result<()> {
+        self.state.send_replace(ProviderState::Updating);
+        let result = self.refresh_inner(cfg).await;
+        match &result {
+            Ok(()) => self.state.send_replace(ProviderState::Ready),
+            Err(err) => self
+                .state
+                .send_replace(ProviderState::Failed(format!("{err:#}"))),
+        };
+        result
+    }
+
+    fn state(&self) -> ProviderState {
+        self.state.borrow().clone()
+    }
+
+    // 索引只提供列表与产物地址，没有已安装版本对比
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            supports_update_check: false,
+            ..ProviderCapabilities::ALL
+        }
+    }
+
+    fn subscribe_state(&self) -> watch::Receiver<ProviderState> {
+        let mut rx = self.state.subscribe();
+        rx.mark_changed();
+        rx
+    }
+
+    async fn get_page(
+        &self,
+        page: u32,
+        limit: u32,
+        search: SearchConfig,
+    ) -> anyhow::Result<Vec<ManifestItemV2>> {
+        if limit == 0 {
+            return Err(anyhow!("limit must be greater than 0"));
+        }
+        Ok(self
+            .filter_items(&search)
+            .into_iter()
+            .skip((page as usize).saturating_mul(limit as usize))
+            .take(limit as usize)
+            .collect())
+    }
+
+    async fn get_categories(&self) -> anyhow::Result<Vec<String>> {
+        Ok(vec![
+            HIDE_PAID.to_string(),
+            HIDE_FORCE_PAID.to_string(),
+            QUICK_APP.to_string(),
+            WATCHFACE.to_string(),
+        ])
+    }
+
+    // 内嵌 manifest 优先，其次 manifest_url，都没有时由列表项与产物表拼出
+    async fn get_item_manifest(&self, item_id: String) -> anyhow::Result<ManifestV2> {
+        let entry = self
+            .entry(&item_id)
+            .ok_or_else(|| anyhow!("item {} not found in index", item_id))?;
+
+        let mut manifest = match (&entry.manifest, &entry.manifest_url) {
+            (Some(manifest), _) => manifest.clone(),
+            (None, Some(url)) => self
+                .fetch_json::<ManifestV2>(&self.resolve_url(url))
+                .await
+                .with_context(|| format!("failed to fetch manifest of {item_id}"))?,
+            (None, None) => ManifestV2 {
+                item: entry.item.clone(),
+                downloads: entry.downloads.clone(),
+                ..Default::default()
+            },
+        };
+        // 与官方源一致：不合规只记录日志，不影响打开详情
+        if let Err(err) = validate_manifest(&manifest, None) {
+            log::warn!("[{}] {err}", self.name);
+        }
+        manifest.item.fill_preview_media();
+
+        self.resolve_item_urls(&mut manifest.item);
+        for download in manifest.downloads.values_mut() {
+            if let Some(url) = download.url.as_mut() {
+                *url = self.resolve_url(url);
+            }
+        }
+        Ok(manifest)
+    }
+
+    async fn download(
+        &self,
+        item_id: String,
+        device: String,
+        progress_cb: Option<Box<dyn Fn(ProgressData) + Send>>,
+    ) -> anyhow::Result<PathBuf> {
+        self.download_inner(item_id, device, progress_cb).await
+    }
+
+    async fn get_total_items(&self) -> anyhow::Result<u64> {
+        Ok(self.entries.load().len() as u64)
+    }
+
+    fn export_config(&self) -> serde_json::Value {
+        let cfg = GenericRefreshConfig {
+            base_url: Some(self.base_url()),
+            index_path: Some(self.index_path()),
+        };
+        serde_json::to_value(cfg).unwrap_or_default()
+    }
+
+    fn apply_config(&self, cfg: serde_json::Value) -> anyhow::Result<()> {
+        let parsed = serde_json::from_value::<GenericRefreshConfig>(cfg.clone())
+            .with_context(|| format!("invalid config: {cfg}"))?;
+        self.apply_urls(parsed);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, routing::get};
+
+    use super::*;
+    use crate::{
+        cache::{FsCacheStore, MemoryCacheStore},
+        test_support::serve,
+    };
+
+    // 产物只给出 file_name（相对 base_url），下载到以 provider 名称分隔的缓存路径
+    #[tokio::test]
+    async fn download_resolves_file_name_against_base_url() {
+        let router = Router::new()
+            .route(
+                "/repo/index.json",
+                get(|| async {
+                    r#"[{"id":"w1","restype":"watchface","name":"Face","description":"",
+                        "preview":[],"icon":"icon.png","cover":"cover.png","author":[],
+                        "downloads":{"default":{"version":"1.0","file_name":"files/face.bin"}}}]"#
+                }),
+            )
+            .route("/repo/files/face.bin", get(|| async { "artifact" }));
+        let base = serve(router).await;
+        let dir = tempfile::tempdir().unwrap();
+        let provider = GenericJsonProvider::new(
+            "Mine",
+            &format!("{base}/repo"),
+            "index.json",
+            Arc::new(FsCacheStore::new(dir.path().to_path_buf())),
+        )
+        .unwrap();
+        provider.refresh("").await.unwrap();
+
+        let path = provider
+            .download("w1".into(), "o66".into(), None)
+            .await
+            .unwrap();
+        assert!(path.starts_with(dir.path().join("Mine").join("w1")));
+        assert_eq!(std::fs::read(&path).unwrap(), b"artifact");
+    }
+
+    // 缺少图标等字段的条目仍可打开详情
+    #[tokio::test]
+    async fn invalid_manifest_still_opens() {
+        let router = Router::new().route(
+            "/repo/index.json",
+            get(|| async {
+                r#"[{"id":"w1","restype":"watchface","name":"Face","description":"",
+                    "preview":[],"icon":"","cover":"","author":[],"downloads":{}}]"#
+            }),
+        );
+        let base = serve(router).await;
+        let provider = GenericJsonProvider::new(
+            "Mine",
+            &format!("{base}/repo"),
+            "index.json",
+            Arc::new(MemoryCacheStore::new()),
+        )
+        .unwrap();
+        provider.refresh("").await.unwrap();
+
+        let manifest = provider.get_item_manifest("w1".into()).await.unwrap();
+        assert_eq!(manifest.item.name, "Face");
+        assert!(manifest.downloads.is_empty());
+    }
+}
@@ -13,7 +13,7 @@ fn parse_optional_u64(value: Option<&serde_json::Value>) -> Option<u64> {
     }
 }
 
-fn map_download_key_v1_to_v2(key: &str) -> String {
+pub fn map_download_key_v1_to_v2(key: &str) -> String {
     // 不需要再维护这个列表了，v1的设备支持到s5和rw6即为终点
     let ret = match key {
         // Xiaomi Watch S3 系列
@@ -193,6 +193,7 @@ pub fn manifest_v1_to_v2(raw: serde_json::Value) -> anyhow::Result<ManifestV2> {
                         logs.push(ManifestDownloadUpdateLogV2 {
                             version: ver.to_string(),
                             content: content.to_string(),
+                            content_i18n: None,
                         });
                     }
                 }
@@ -211,6 +212,7 @@ pub fn manifest_v1_to_v2(raw: serde_json::Value) -> anyhow::Result<ManifestV2> {
                     sha256,
                     display_name,
                     updatelogs,
+                    unknown: Default::default(),
                 },
             );
         }
@@ -221,5 +223,8 @@ pub fn manifest_v1_to_v2(raw: serde_json::Value) -> anyhow::Result<ManifestV2> {
         links: links_v2,
         downloads: downloads_v2,
         ext,
+        schema_version: None,
+        legacy: true,
+        unknown: Default::default(),
     })
 }
@@ -1,7 +1,9 @@
-use std::collections::HashMap;
+use std::{cmp::Ordering, collections::HashMap};
 
 use serde::{Deserialize, Serialize};
 
+use crate::community::models::official::DeviceChipV2;
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ProgressData {
     pub progress: f32,
@@ -11,31 +13,326 @@ pub struct ProgressData {
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub enum ProviderState {
     Ready,
+    ReadyStale, // 使用磁盘缓存的数据，可能已过时
     Updating,
+    Partial(String), // 索引可用，但设备表或探索页加载失败
     Failed(String),
 }
 
+// provider 支持的操作，聚合层与前端据此跳过不支持的调用、隐藏无关界面
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ProviderCapabilities {
+    pub supports_search: bool,       // get_page 会按 SearchConfig.filter 过滤
+    pub supports_categories: bool,   // get_categories 返回有意义的分类
+    pub supports_download: bool,     // download 可用
+    pub supports_update_check: bool, // 可对比已安装版本检查更新
+    pub paginated: bool,             // get_page 按 page/limit 分页，否则第 0 页即全部结果
+}
+
+impl ProviderCapabilities {
+    pub const ALL: Self = Self {
+        supports_search: true,
+        supports_categories: true,
+        supports_download: true,
+        supports_update_check: true,
+        paginated: true,
+    };
+}
+
+impl Default for ProviderCapabilities {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+// 聚合状态订阅中的一条状态变化
 #[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProviderStateEvent {
+    pub provider: String,
+    pub state: ProviderState,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct SearchConfig {
     pub filter: Option<String>,
     pub sort: SortRuleV2,
+    #[serde(default)]
+    pub order: Option<SortOrder>, // 缺省时按排序规则的默认方向
+    #[serde(default)]
+    pub seed: Option<u64>, // 随机排序种子，缺省时使用会话种子（见 reshuffle）
     pub category: Option<Vec<String>>,
+    #[serde(default)]
+    pub chip: Option<DeviceChipV2>, // 仅保留支持该芯片设备的资源
+    #[serde(default)]
+    pub exclude_tags: Option<Vec<String>>, // 排除带有这些标签的资源（大小写不敏感）
+    #[serde(default)]
+    pub exclude_restype: Option<Vec<ResourceTypeV2>>,
+    #[serde(default)]
+    pub keyword_mode: KeywordMode, // 多个关键词之间的组合方式
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+// 关键词按空白切分，`"..."` 内视为一个完整短语
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeywordMode {
+    #[default]
+    All, // 每个关键词都需命中
+    Any, // 命中任一关键词即可
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum SortRuleV2 {
+    #[default]
     Random,
     Name,
     Time,
 }
 
+impl SortRuleV2 {
+    // 名称默认 A→Z，时间默认最新在前，随机不区分方向
+    pub fn default_order(self) -> SortOrder {
+        match self {
+            SortRuleV2::Random | SortRuleV2::Name => SortOrder::Asc,
+            SortRuleV2::Time => SortOrder::Desc,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    Desc,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+pub enum VerifyStatus {
+    Present, // 存在且校验通过
+    Missing, // 未缓存
+    Corrupt, // 校验不一致，需要重新下载
+    Unknown, // manifest 未提供 sha256，无法校验
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TagInfo {
+    pub name: String,
+    pub count: u64, // 含该标签的资源数
+}
+
+// 单个资源的更新检查结果
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UpdateInfo {
+    pub update_available: bool,
+    pub latest_version: String,
+    pub updatelogs: Vec<ManifestDownloadUpdateLogV2>, // 比已安装版本新的更新日志
+}
+
+// 已安装资源的引用，用于批量检查更新
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct InstalledRef {
+    pub item_id: String,
+    pub device: String,
+    pub version: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UpdateAvailable {
+    pub item_id: String,
+    pub device: String,
+    pub installed_version: String,
+    pub status: UpdateStatus,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub enum UpdateStatus {
+    Available(String), // 仓库中的新版本号
+    Removed,           // 已从索引中下架
+    Failed(String),    // manifest 获取失败等
+}
+
+// 批量预取 manifest 的结果
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct PrefetchReport {
+    pub fetched: Vec<String>, // 本次拉取（或从磁盘载入）并写入缓存的资源 id
+    pub cached: Vec<String>,  // 内存中已有缓存而跳过的资源 id
+    pub failed: Vec<(String, String)>, // (资源 id, 错误信息)
+}
+
+// 已下载到缓存中的资源
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CachedItem {
+    pub item_id: String,
+    pub files: Vec<(String, u64)>, // (文件名, 字节数)
+}
+
+// 聚合视图中的列表项，附带来源 provider
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SourcedItem {
+    pub provider: String,
+    pub item: ManifestItemV2,
+}
+
+impl SourcedItem {
+    // `provider:item_id`，用于聚合视图中的详情与下载
+    pub fn qualified_id(&self) -> String {
+        crate::community::federated::qualify_id(&self.provider, &self.item.id)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProviderFailure {
+    pub provider: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct FederatedPage {
+    pub items: Vec<SourcedItem>,
+    pub errors: Vec<ProviderFailure>, // 查询失败的 provider，不影响其他结果
+}
+
+// 带命中位置的搜索结果，供前端高亮
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SearchHit {
+    pub item: ManifestItemV2,
+    pub matches: Vec<SearchMatch>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SearchMatch {
+    pub field: MatchField,
+    pub value: String,                       // 命中的原文（名称或某个标签）
+    pub ranges: Vec<std::ops::Range<usize>>, // 原文中的字节区间
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MatchField {
+    Name,
+    Tag,
+}
+
+// 分页结果，统计均基于过滤后的条目而非索引总数
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub page: u32,
+    pub limit: u32,
+    pub total_items: u64,
+    pub total_pages: u64,
+    pub has_next: bool,
+}
+
+pub type PageResult = Page<ManifestItemV2>;
+
+// 游标分页结果；next_cursor 为 None 表示没有更多
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CursorPage<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+pub const SUPPORTED_MANIFEST_SCHEMA: u32 = 2; // 可完整理解的 manifest 规范主版本
+
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
 pub struct ManifestV2 {
     pub item: ManifestItemV2,
     pub links: Vec<ManifestLinkV2>,
     pub downloads: HashMap<String, ManifestDownloadV2>,
     pub ext: serde_json::Value,
+    #[serde(
+        default,
+        rename = "schemaVersion",
+        alias = "schema_version",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub schema_version: Option<u32>,
+    // 由 v1 manifest.json 转换而来，供界面提示"旧版资源"
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub legacy: bool,
+    // 规范新增而本版本尚未识别的字段，原样保留以便经桥接/磁盘缓存往返不丢失
+    #[serde(flatten)]
+    pub unknown: serde_json::Map<String, serde_json::Value>,
+}
+
+// ext 中已知的扩展字段；未知键保留在 extra 中，原始值仍可通过 ManifestV2.ext 访问
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ManifestExtV2 {
+    #[serde(rename = "trialDownloads", skip_serializing_if = "Option::is_none")]
+    pub trial_downloads: Option<HashMap<String, ManifestDownloadV2>>, // 试用版产物
+    #[serde(rename = "minAppVersion", skip_serializing_if = "Option::is_none")]
+    pub min_app_version: Option<String>, // 要求的最低 AstroBox 版本
+    #[serde(rename = "packageName", skip_serializing_if = "Option::is_none")]
+    pub package_name: Option<String>, // 快应用包名
+    #[serde(rename = "previewAspectRatio", skip_serializing_if = "Option::is_none")]
+    pub preview_aspect_ratio: Option<f64>, // 表盘预览图宽高比
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ManifestV2 {
+    // 不依赖索引的自检，供仓库工具在发布前检查 manifest；每个问题各对应一个错误
+    pub fn validate(&self) -> Result<(), Vec<crate::community::error::ManifestValidationError>> {
+        crate::community::validate::validate_manifest(self, None).map_err(|err| {
+            err.violations
+                .into_iter()
+                .map(
+                    |violation| crate::community::error::ManifestValidationError {
+                        item_id: err.item_id.clone(),
+                        violations: vec![violation],
+                    },
+                )
+                .collect()
+        })
+    }
+
+    // 声明的规范主版本高于本版本支持的版本时，部分字段可能未被识别
+    pub fn is_newer_schema(&self) -> bool {
+        self.schema_version
+            .is_some_and(|version| version > SUPPORTED_MANIFEST_SCHEMA)
+    }
+
+    // 本地化条目信息及所有更新日志
+    pub fn localize(&mut self, lang: &str) {
+        self.item.localize(lang);
+        for download in self.downloads.values_mut() {
+            for log in download.updatelogs.iter_mut().flatten() {
+                if let Some(content) = pick_localized(log.content_i18n.as_ref(), lang) {
+                    log.content = content.to_string();
+                }
+            }
+        }
+    }
+
+    // 逐字段解析，出错时指明是哪个字段
+    pub fn parsed_ext(&self) -> anyhow::Result<ManifestExtV2> {
+        let mut extra = match &self.ext {
+            serde_json::Value::Null => return Ok(ManifestExtV2::default()),
+            serde_json::Value::Object(map) => map.clone(),
+            other => anyhow::bail!("ext must be an object, got {other}"),
+        };
+
+        fn take<T: serde::de::DeserializeOwned>(
+            map: &mut serde_json::Map<String, serde_json::Value>,
+            key: &str,
+        ) -> anyhow::Result<Option<T>> {
+            match map.remove(key) {
+                None | Some(serde_json::Value::Null) => Ok(None),
+                Some(value) => serde_json::from_value(value)
+                    .map(Some)
+                    .map_err(|err| anyhow::anyhow!("malformed ext.{key}: {err}")),
+            }
+        }
+
+        Ok(ManifestExtV2 {
+            trial_downloads: take(&mut extra, "trialDownloads")?,
+            min_app_version: take(&mut extra, "minAppVersion")?,
+            package_name: take(&mut extra, "packageName")?,
+            preview_aspect_ratio: take(&mut extra, "previewAspectRatio")?,
+            extra,
+        })
+    }
 }
 
 #[derive(Debug, Default, Clone, Deserialize, Serialize)]
@@ -44,12 +341,130 @@ pub struct ManifestItemV2 {
     pub restype: ResourceTypeV2,
     pub name: String,
     pub description: String,
-    pub preview: Vec<String>,
+    pub preview: Vec<String>, // 仅图片，保留供旧版客户端使用
+    // 带类型的预览（图片/视频）；旧 manifest 没有此字段时由 preview 补齐，见 fill_preview_media
+    #[serde(
+        default,
+        rename = "previewMedia",
+        alias = "preview_media",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub preview_media: Vec<PreviewMediaV2>,
     pub icon: String,
     pub cover: String,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub paid_type: Option<PaidTypeV2>,
     pub author: Vec<ManifestAuthorV2>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>, // 来自索引，供列表卡片展示标签
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub devices: Vec<String>, // 来自索引，支持的设备 id
+    // 多语言名称/描述，键为语言标签（如 `zh-CN`、`en`）；原样保留供界面切换语言
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name_i18n: Option<HashMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description_i18n: Option<HashMap<String, String>>,
+    #[serde(flatten)]
+    pub unknown: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ManifestItemV2 {
+    // preview_media 为空时把 preview 中的图片作为 Image 条目补入
+    pub fn fill_preview_media(&mut self) {
+        if self.preview_media.is_empty() {
+            self.preview_media = self
+                .preview
+                .iter()
+                .filter(|url| !url.trim().is_empty())
+                .map(|url| PreviewMediaV2 {
+                    kind: PreviewMediaKind::Image,
+                    url: url.clone(),
+                })
+                .collect();
+        }
+    }
+
+    // 指定语言的名称，无对应翻译时返回基础 name
+    pub fn localized_name(&self, lang: &str) -> &str {
+        pick_localized(self.name_i18n.as_ref(), lang).unwrap_or(&self.name)
+    }
+
+    pub fn localized_description(&self, lang: &str) -> &str {
+        pick_localized(self.description_i18n.as_ref(), lang).unwrap_or(&self.description)
+    }
+
+    // 按语言替换 name/description，无对应翻译时保留基础字段
+    pub fn localize(&mut self, lang: &str) {
+        if let Some(name) = pick_localized(self.name_i18n.as_ref(), lang) {
+            self.name = name.to_string();
+        }
+        if let Some(description) = pick_localized(self.description_i18n.as_ref(), lang) {
+            self.description = description.to_string();
+        }
+    }
+}
+
+// 先精确匹配语言标签（忽略大小写与 `_`/`-` 差异），再按主语言匹配（`zh-CN` 可用 `zh` / `zh-TW`）
+pub fn pick_localized<'a>(map: Option<&'a HashMap<String, String>>, lang: &str) -> Option<&'a str> {
+    let map = map?;
+    let normalize = |tag: &str| tag.trim().replace('_', "-").to_lowercase();
+    let lang = normalize(lang);
+    if lang.is_empty() {
+        return None;
+    }
+    let primary = lang.split('-').next().unwrap_or(&lang).to_string();
+
+    let mut fallback = None;
+    for (tag, value) in map {
+        if value.trim().is_empty() {
+            continue;
+        }
+        let tag = normalize(tag);
+        if tag == lang {
+            return Some(value.as_str());
+        }
+        if tag.split('-').next() == Some(primary.as_str()) {
+            // 主语言本身（`zh`）优先于其他地区变体
+            if tag == primary || fallback.is_none() {
+                fallback = Some(value.as_str());
+            }
+        }
+    }
+    fallback
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PreviewMediaKind {
+    Image,
+    Video,
+}
+
+// 预览媒体；previewMedia 中直接写字符串时视为图片
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+#[serde(from = "PreviewMediaRepr")]
+pub struct PreviewMediaV2 {
+    pub kind: PreviewMediaKind,
+    pub url: String,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PreviewMediaRepr {
+    Url(String),
+    Typed { kind: PreviewMediaKind, url: String },
+}
+
+impl From<PreviewMediaRepr> for PreviewMediaV2 {
+    fn from(repr: PreviewMediaRepr) -> Self {
+        match repr {
+            PreviewMediaRepr::Url(url) => Self {
+                kind: PreviewMediaKind::Image,
+                url,
+            },
+            PreviewMediaRepr::Typed { kind, url } => Self { kind, url },
+        }
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -82,15 +497,87 @@ pub struct ManifestDownloadV2 {
     pub display_name: Option<String>,
     #[serde(default)]
     pub updatelogs: Option<Vec<ManifestDownloadUpdateLogV2>>,
+    #[serde(flatten)]
+    pub unknown: serde_json::Map<String, serde_json::Value>,
+}
+
+impl ManifestDownloadV2 {
+    // 已安装版本之后的更新日志。版本号可解析时按版本比较（兼容新→旧、旧→新两种排列），
+    // 否则退化为返回匹配条目之后的全部日志；找不到匹配条目时返回全部
+    pub fn logs_since(&self, version: &str) -> &[ManifestDownloadUpdateLogV2] {
+        let logs = self.updatelogs.as_deref().unwrap_or_default();
+        let newer = |log: &ManifestDownloadUpdateLogV2| {
+            crate::version::compare(&log.version, version).map(|ord| ord == Ordering::Greater)
+        };
+
+        if let Some(flags) = logs.iter().map(newer).collect::<Option<Vec<_>>>() {
+            let descending = match (logs.first(), logs.last()) {
+                (Some(first), Some(last)) => {
+                    crate::version::compare(&first.version, &last.version)
+                        == Some(Ordering::Greater)
+                }
+                _ => false,
+            };
+            return if descending {
+                let end = flags.iter().position(|newer| !newer).unwrap_or(logs.len());
+                &logs[..end]
+            } else {
+                let start = flags.iter().position(|newer| *newer).unwrap_or(logs.len());
+                &logs[start..]
+            };
+        }
+
+        match logs
+            .iter()
+            .position(|log| log.version.trim() == version.trim())
+        {
+            Some(idx) => &logs[idx + 1..],
+            None => logs,
+        }
+    }
+
+    // 去重并按版本从新到旧排列的更新日志。同一版本（1.2 与 v1.2.0 视为相同）只保留内容最长的一条；
+    // 无法解析的版本号排在最后并保持原有顺序
+    pub fn sorted_updatelogs(&self) -> Vec<&ManifestDownloadUpdateLogV2> {
+        let logs = self.updatelogs.as_deref().unwrap_or_default();
+        let same_version = |a: &str, b: &str| match crate::version::compare(a, b) {
+            Some(ord) => ord == Ordering::Equal,
+            None => a.trim() == b.trim(),
+        };
+
+        let mut unique: Vec<&ManifestDownloadUpdateLogV2> = Vec::with_capacity(logs.len());
+        for log in logs {
+            match unique
+                .iter_mut()
+                .find(|kept| same_version(&kept.version, &log.version))
+            {
+                Some(kept) if log.content.len() > kept.content.len() => *kept = log,
+                Some(_) => {}
+                None => unique.push(log),
+            }
+        }
+
+        // 可解析的版本之间是全序，只对它们排序
+        let (mut parsed, unparsed): (Vec<_>, Vec<_>) = unique
+            .into_iter()
+            .partition(|log| crate::version::compare(&log.version, &log.version).is_some());
+        parsed.sort_by(|a, b| {
+            crate::version::compare(&b.version, &a.version).unwrap_or(Ordering::Equal)
+        });
+        parsed.extend(unparsed);
+        parsed
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ManifestDownloadUpdateLogV2 {
     pub version: String,
     pub content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_i18n: Option<HashMap<String, String>>,
 }
 
-#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize, PartialEq, Eq, Hash)]
 pub enum ResourceTypeV2 {
     #[default]
     #[serde(rename = "quick_app")]
@@ -114,3 +601,134 @@ pub enum PaidTypeV2 {
     #[serde(rename = "force_paid")]
     ForcePaid, // 强制付费（不给钱不让用）
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn download_with_logs(logs: &[(&str, &str)]) -> ManifestDownloadV2 {
+        ManifestDownloadV2 {
+            version: String::new(),
+            file_name: String::new(),
+            version_code: None,
+            url: None,
+            sha256: None,
+            display_name: None,
+            updatelogs: Some(
+                logs.iter()
+                    .map(|(version, content)| ManifestDownloadUpdateLogV2 {
+                        version: version.to_string(),
+                        content: content.to_string(),
+                        content_i18n: None,
+                    })
+                    .collect(),
+            ),
+            unknown: Default::default(),
+        }
+    }
+
+    fn versions<'a>(
+        logs: impl IntoIterator<Item = &'a ManifestDownloadUpdateLogV2>,
+    ) -> Vec<&'a str> {
+        logs.into_iter().map(|log| log.version.as_str()).collect()
+    }
+
+    #[test]
+    fn logs_since_compares_versions_in_either_order() {
+        let descending = download_with_logs(&[("v1.3", ""), ("1.2.1", ""), ("1.2", "")]);
+        assert_eq!(versions(descending.logs_since("1.2.0")), ["v1.3", "1.2.1"]);
+        assert!(descending.logs_since("V1.3.0").is_empty());
+
+        let ascending = download_with_logs(&[("1.2", ""), ("1.2.1", ""), ("v1.3", "")]);
+        assert_eq!(versions(ascending.logs_since("v1.2")), ["1.2.1", "v1.3"]);
+        assert_eq!(versions(ascending.logs_since("1.1")).len(), 3);
+    }
+
+    #[test]
+    fn logs_since_falls_back_to_position_for_non_numeric_versions() {
+        let download = download_with_logs(&[("beta-a", ""), ("beta-b", ""), ("beta-c", "")]);
+        assert_eq!(
+            versions(download.logs_since(" beta-a ")),
+            ["beta-b", "beta-c"]
+        );
+        assert_eq!(versions(download.logs_since("unknown")).len(), 3);
+    }
+
+    #[test]
+    fn sorted_updatelogs_dedups_and_orders() {
+        let download = download_with_logs(&[
+            ("1.2", "short"),
+            ("nightly", "n"),
+            ("1.10", "ten"),
+            ("v1.2.0", "longer text"),
+            ("1.9", "nine"),
+            ("1.2.0", "mid"),
+        ]);
+        let sorted = download.sorted_updatelogs();
+        assert_eq!(
+            versions(sorted.iter().copied()),
+            ["1.10", "1.9", "v1.2.0", "nightly"]
+        );
+        assert_eq!(sorted[2].content, "longer text");
+    }
+
+    #[test]
+    fn validate_reports_each_violation_separately() {
+        let manifest = ManifestV2 {
+            item: ManifestItemV2 {
+                id: "w1".into(),
+                name: "Face".into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let errors = manifest.validate().unwrap_err();
+        let fields = errors
+            .iter()
+            .map(|err| {
+                assert_eq!(err.item_id, "w1");
+                assert_eq!(err.violations.len(), 1);
+                err.violations[0].field.as_str()
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(fields, ["downloads", "item.icon"]);
+    }
+
+    // 未识别的字段经反序列化、再序列化后原样保留，空的 tags/devices 不额外输出
+    #[test]
+    fn manifest_round_trip_keeps_unknown_fields() {
+        let raw = serde_json::json!({
+            "item": {
+                "id": "w1",
+                "restype": "watchface",
+                "name": "Black Face",
+                "description": "",
+                "preview": ["a.png"],
+                "icon": "icon.png",
+                "cover": "cover.png",
+                "author": [{ "name": "someone", "bindABAccount": false }],
+                "futureItemField": { "nested": [1, 2] },
+            },
+            "links": [{ "icon": null, "title": "home", "url": "https://example.com" }],
+            "downloads": {
+                "o66": {
+                    "version": "1.0.0",
+                    "file_name": "w1.bin",
+                    "versionCode": 100,
+                    "url": "https://example.com/w1.bin",
+                    "sha256": "00",
+                    "display_name": "Xiaomi Watch S4",
+                    "updatelogs": [{ "version": "1.0.0", "content": "init" }],
+                    "futureDownloadField": true,
+                },
+            },
+            "ext": { "anything": "goes" },
+            "schemaVersion": 2,
+            "futureTopLevel": "kept",
+        });
+
+        let manifest: ManifestV2 = serde_json::from_value(raw.clone()).unwrap();
+        assert!(manifest.item.tags.is_empty());
+        assert_eq!(serde_json::to_value(&manifest).unwrap(), raw);
+    }
+}
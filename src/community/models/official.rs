@@ -1,4 +1,4 @@
-use crate::community::models::common::*;
+use crate::{cdn::GitHubCdn, community::models::common::*};
 use std::collections::HashMap;
 
 use serde::{Deserialize, Deserializer, Serialize};
@@ -8,9 +8,30 @@ where
     D: Deserializer<'de>,
 {
     let s: String = Deserialize::deserialize(deserializer)?;
-    Ok(s.split(';').map(|x| x.trim().to_string()).collect())
+    // 空单元格或多余的分号不产生空字符串
+    Ok(s.split(';')
+        .map(str::trim)
+        .filter(|x| !x.is_empty())
+        .map(str::to_string)
+        .collect())
 }
 
+// index_v2.csv 必须包含的列；多出的列会被忽略，便于上游平滑新增字段
+pub const INDEX_V2_REQUIRED_COLUMNS: &[&str] = &[
+    "id",
+    "name",
+    "restype",
+    "repo_owner",
+    "repo_name",
+    "repo_commit_hash",
+    "icon",
+    "cover",
+    "tags",
+    "device_vendors",
+    "devices",
+    "paid_type",
+];
+
 // V2 规范: https://affine.astralsight.space/workspace/af61c26a-3d53-46ca-85e7-89772913da6d/VVn-o4ALtyuf6NbdenmjJ
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct IndexV2 {
@@ -46,10 +67,55 @@ pub struct DeviceV2 {
     pub fetch: bool,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize, Serialize)]
 pub enum DeviceChipV2 {
     #[serde(rename = "xring")]
     XRing,
     #[serde(rename = "bes")]
     Bes,
 }
+
+// 一次索引解析的统计
+#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+pub struct RefreshReport {
+    pub parsed_rows: usize,
+    pub skipped_rows: Vec<SkippedIndexRow>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SkippedIndexRow {
+    pub row: u64, // CSV 行号（表头为第 1 行）
+    pub reason: String,
+}
+
+// 索引新鲜度：最近一次成功刷新的时间与上游数据时间，随离线缓存一同持久化
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct RefreshInfo {
+    pub last_refresh_at: Option<u64>, // 毫秒级 Unix 时间戳
+    pub item_count: usize,
+    pub upstream_modified: Option<String>, // 上游 index_v2.csv 的 Last-Modified
+}
+
+// 调试/状态面板用的概览，全部来自内存数据
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProviderSnapshot {
+    pub total_items: u64,
+    pub items_by_restype: HashMap<ResourceTypeV2, u64>,
+    pub items_by_vendor: HashMap<String, u64>,
+    pub device_count: u64,
+    pub state: ProviderState,
+    pub last_refresh_at: Option<u64>, // 毫秒级 Unix 时间戳
+    pub cdn: GitHubCdn,
+    pub offline: bool,
+}
+
+// 支持排查用的完整内存数据导出（解析后的索引行、设备表、探索页），原样反映 provider 看到的内容
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct DebugDump {
+    pub index_csv_rows: Vec<IndexV2>,
+    pub device_map: DeviceMapV2,
+    pub explore: serde_json::Value,
+    pub cdn: GitHubCdn,
+    pub state: ProviderState,
+    pub skipped_rows: Vec<SkippedIndexRow>, // 最近一次刷新中被跳过的索引行
+}
@@ -0,0 +1,476 @@
+// 官方 v1 仓库的兼容 provider：读取 v1 索引并转换为 v2 索引项，
+// manifest 经 legacyparse 转换（含设备 key 映射），拉取、缓存与下载复用 OfficialV2Provider 的实现。
+// 已迁移到 v2 的资源由 OfficialV2 提供，这里不再重复列出
+use std::sync::Arc;
+
+use anyhow::{Context, anyhow};
+use arc_swap::ArcSwap;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cache::CacheStore,
+    cdn::GitHubCdn,
+    community::{
+        CommunityProvider,
+        error::ProviderError,
+        get_official_v2_provider,
+        legacyparse::map_download_key_v1_to_v2,
+        models::{
+            common::{
+                ManifestItemV2, ManifestV2, PaidTypeV2, ProgressData, ProviderCapabilities,
+                ProviderState, ResourceTypeV2, SearchConfig,
+            },
+            official::IndexV2,
+        },
+        officialv2::{OfficialV2Provider, sanitize_index_csv},
+    },
+};
+
+pub const PROVIDER_NAME: &str = "OfficialV1";
+
+const DEFAULT_REPO_OWNER: &str = "AstralSightStudios";
+const DEFAULT_REPO_NAME: &str = "AstroBox-Repo";
+const DEFAULT_REPO_REF: &str = "refs/heads/main";
+const DEFAULT_INDEX_PATH: &str = "index.csv";
+
+// refresh 的 cfg，同时是 export_config / apply_config 的格式；cdn 等其余字段与 OfficialV2Provider 相同
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+struct V1RefreshConfig {
+    #[serde(default)]
+    cdn: Option<GitHubCdn>,
+    #[serde(default)]
+    repo_owner: Option<String>,
+    #[serde(default)]
+    repo_name: Option<String>,
+    #[serde(default)]
+    repo_ref: Option<String>,
+    // 路径结构与 raw.githubusercontent.com 相同的自建源，同时用于拉取各资源的 manifest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    raw_base_url: Option<String>,
+    #[serde(default)]
+    index_path: Option<String>,
+}
+
+impl V1RefreshConfig {
+    fn parse(cfg: &str) -> anyhow::Result<Self> {
+        if cfg.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        serde_json::from_str(cfg).with_context(|| format!("invalid refresh config: {cfg}"))
+    }
+}
+
+// v1 索引的一行；列名沿用 v2 的写法，同时接受 v1 常见的别名，缺失的可选列按空值处理
+#[derive(Debug, Deserialize)]
+struct IndexV1Row {
+    id: String,
+    name: String,
+    #[serde(alias = "type")]
+    restype: String,
+    repo_owner: String,
+    repo_name: String,
+    #[serde(alias = "commit", alias = "repo_commit")]
+    repo_commit_hash: String,
+    #[serde(default)]
+    icon: String,
+    #[serde(default)]
+    cover: String,
+    #[serde(default)]
+    tags: String,
+    #[serde(default, alias = "device")]
+    devices: String,
+    #[serde(default)]
+    paid_type: String,
+}
+
+impl IndexV1Row {
+    fn into_index_v2(self) -> anyhow::Result<IndexV2> {
+        let restype = serde_json::from_value::<ResourceTypeV2>(self.restype.trim().into())
+            .map_err(|_| anyhow!("unknown resource type `{}`", self.restype))?;
+        let paid_type = match self.paid_type.trim() {
+            "" => PaidTypeV2::Free,
+            value => serde_json::from_value::<PaidTypeV2>(value.into()).unwrap_or(PaidTypeV2::Free),
+        };
+        let devices = split_list(&self.devices)
+            .iter()
+            .map(|key| map_download_key_v1_to_v2(key))
+            .collect();
+        Ok(IndexV2 {
+            cover: if self.cover.trim().is_empty() {
+                self.icon.clone()
+            } else {
+                self.cover
+            },
+            id: self.id,
+            name: self.name,
+            restype,
+            repo_owner: self.repo_owner,
+            repo_name: self.repo_name,
+            repo_commit_hash: self.repo_commit_hash,
+            icon: self.icon,
+            tags: split_list(&self.tags),
+            device_vendors: Vec::new(),
+            devices,
+            paid_type,
+        })
+    }
+}
+
+// v1 的列表列以 `;` 或 `,` 分隔
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split([';', ','])
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+pub struct OfficialV1Provider {
+    inner: OfficialV2Provider,
+    last_refresh_cfg: ArcSwap<V1RefreshConfig>, // 冷却判断与 export_config 均使用最近一次的配置
+}
+
+impl OfficialV1Provider {
+    pub fn new(cdn: GitHubCdn, store: Arc<dyn CacheStore>) -> Self {
+        Self {
+            inner: OfficialV2Provider::new(cdn, store),
+            last_refresh_cfg: ArcSwap::new(Arc::new(V1RefreshConfig::default())),
+        }
+    }
+
+    pub fn inner(&self) -> &OfficialV2Provider {
+        &self.inner
+    }
+
+    // 同 CommunityProvider::refresh；force 为 true 时忽略冷却时间
+    pub async fn refresh_with(&self, cfg: &str, force: bool) -> anyhow::Result<()> {
+        let parsed = V1RefreshConfig::parse(cfg)?;
+        // 冷却规则与 OfficialV2 相同，冷却时间也由内部 provider 的 set_refresh_cooldown 设置
+        let remaining = self.inner.refresh_cooldown_remaining();
+        if !force && parsed == **self.last_refresh_cfg.load() && !remaining.is_zero() {
+            log::debug!("[OfficialV1] refresh called during cooldown, {remaining:?} left");
+            return Err(ProviderError::Cooldown(remaining).into());
+        }
+        self.inner.mark_refresh_started();
+        self.last_refresh_cfg.store(Arc::new(parsed.clone()));
+
+        self.inner.set_state(ProviderState::Updating);
+        let result = self.refresh_inner(&parsed).await;
+        if let Err(err) = &result {
+            self.inner
+                .set_state(ProviderState::Failed(format!("v1 索引加载失败: {err:#}")));
+        }
+        result
+    }
+
+    async fn refresh_inner(&self, cfg: &V1RefreshConfig) -> anyhow::Result<()> {
+        let pick = |value: &Option<String>, default: &str| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .unwrap_or(default)
+                .to_string()
+        };
+        let cdn = cfg.cdn.unwrap_or(GitHubCdn::Raw);
+        self.inner.set_cdn(cdn);
+        self.inner.set_raw_base_url(cfg.raw_base_url.as_deref());
+        let raw_url = format!(
+            "{}/{}",
+            self.inner.build_repo_raw_url(
+                &pick(&cfg.repo_owner, DEFAULT_REPO_OWNER),
+                &pick(&cfg.repo_name, DEFAULT_REPO_NAME),
+                pick(&cfg.repo_ref, DEFAULT_REPO_REF).trim_matches('/'),
+            ),
+            pick(&cfg.index_path, DEFAULT_INDEX_PATH).trim_start_matches('/'),
+        );
+        let url = cdn.convert_url(&raw_url);
+
+        self.inner.ensure_online()?;
+        let body = self
+            .inner
+            .http_client()
+            .get(&url)
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status())
+            .with_context(|| format!("failed to fetch {url}"))?
+            .bytes()
+            .await
+            .with_context(|| format!("failed to read {url}"))?;
+        let list = self.parse_index(&sanitize_index_csv(&body)).await?;
+        if list.is_empty() {
+            return Err(anyhow!("v1 index {url} has no usable rows"));
+        }
+        self.inner.load_index(list);
+        Ok(())
+    }
+
+    // 解析失败的行只跳过；已在 v2 索引中出现的资源以 v2 为准
+    async fn parse_index(&self, csv_text: &str) -> anyhow::Result<Vec<IndexV2>> {
+        let v2 = get_official_v2_provider().await;
+        let mut reader = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .flexible(true)
+            .from_reader(csv_text.as_bytes());
+
+        let mut list = Vec::new();
+        let mut migrated = 0usize;
+        for row in reader.deserialize::<IndexV1Row>() {
+            let item = match row
+                .map_err(anyhow::Error::from)
+                .and_then(IndexV1Row::into_index_v2)
+            {
+                Ok(item) => item,
+                Err(err) => {
+                    log::warn!("[OfficialV1] skipped malformed index row: {err:#}");
+                    continue;
+                }
+            };
+            if v2.as_ref().is_some_and(|v2| v2.has_item(&item.id)) {
+                migrated += 1;
+                continue;
+            }
+            list.push(item);
+        }
+        if migrated > 0 {
+            log::info!("[OfficialV1] {migrated} items already provided by OfficialV2");
+        }
+        Ok(list)
+    }
+}
+
+#[async_trait]
+impl CommunityProvider for OfficialV1Provider {
+    fn provider_name(&self) -> String {
+        PROVIDER_NAME.to_string()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn into_any_arc(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
+        self
+    }
+
+    // cfg 可选 `cdn`、`repo_owner` / `repo_name` / `repo_ref`、`raw_base_url` 与 `index_path`
+    async fn refresh(&self, cfg: &str) -> anyhow::Result<()> {
+        self.refresh_with(cfg, false).await
+    }
+
+    fn state(&self) -> ProviderState {
+        self.inner.state()
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        self.inner.capabilities()
+    }
+
+    fn subscribe_state(&self) -> tokio::sync::watch::Receiver<ProviderState> {
+        self.inner.subscribe_state()
+    }
+
+    async fn get_page(
+        &self,
+        page: u32,
+        limit: u32,
+        search: SearchConfig,
+    ) -> anyhow::Result<Vec<ManifestItemV2>> {
+        self.inner.get_page(page, limit, search).await
+    }
+
+    async fn get_categories(&self) -> anyhow::Result<Vec<String>> {
+        self.inner.get_categories().await
+    }
+
+    // OfficialV2 的 manifest 拉取在 manifest_v2.json 缺失时回退 manifest.json 并经 legacyparse 转换
+    async fn get_item_manifest(&self, item_id: String) -> anyhow::Result<ManifestV2> {
+        self.inner.get_item_manifest(item_id).await
+    }
+
+    async fn download(
+        &self,
+        item_id: String,
+        device: String,
+        progress_cb: Option<Box<dyn Fn(ProgressData) + Send>>,
+    ) -> anyhow::Result<std::path::PathBuf> {
+        self.inner.download(item_id, device, progress_cb).await
+    }
+
+    async fn get_total_items(&self) -> anyhow::Result<u64> {
+        self.inner.get_total_items().await
+    }
+
+    async fn get_items_by_ids(
+        &self,
+        ids: &[String],
+    ) -> anyhow::Result<Vec<Option<ManifestItemV2>>> {
+        self.inner.get_items_by_ids(ids).await
+    }
+
+    async fn suggest(&self, prefix: &str, limit: usize) -> anyhow::Result<Vec<String>> {
+        self.inner.suggest(prefix, limit).await
+    }
+
+    async fn probe_download_size(
+        &self,
+        item_id: String,
+        device: String,
+    ) -> anyhow::Result<Option<u64>> {
+        self.inner.probe_download_size(item_id, device).await
+    }
+
+    // 仓库与索引路径只保存在配置里，直接导出最近一次 refresh / apply_config 的配置
+    fn export_config(&self) -> serde_json::Value {
+        serde_json::to_value(&**self.last_refresh_cfg.load()).unwrap_or_default()
+    }
+
+    // 只更新设置，不联网
+    fn apply_config(&self, cfg: serde_json::Value) -> anyhow::Result<()> {
+        let parsed: V1RefreshConfig = serde_json::from_value(cfg.clone())
+            .with_context(|| format!("invalid config: {cfg}"))?;
+        self.inner.set_cdn(parsed.cdn.unwrap_or(GitHubCdn::Raw));
+        self.inner.set_raw_base_url(parsed.raw_base_url.as_deref());
+        self.last_refresh_cfg.store(Arc::new(parsed));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, routing::get};
+
+    use super::*;
+    use crate::{
+        cache::MemoryCacheStore,
+        community::{add_official_v1_provider, get_official_v1_provider, isolated_registry},
+        test_support::serve,
+    };
+
+    const INDEX_V1_CSV: &str = "\
+id,name,type,repo_owner,repo_name,commit,icon,device
+f1,Old Face,watchface,someone,face,abc,icon.png,o66
+";
+    // v1 manifest 没有 id 与 restype，设备 key 为旧代号
+    const MANIFEST_V1: &str = r#"{
+        "item": { "name": "Old Face", "description": "", "preview": ["p.png"], "icon": "icon.png", "author": [] },
+        "downloads": { "o66": { "version": "1.0", "file_name": "face.bin" } }
+    }"#;
+
+    #[tokio::test]
+    async fn v1_manifest_loads_through_provider() {
+        let _registry = isolated_registry().await;
+        let router = Router::new()
+            .route("/o/r/main/index.csv", get(|| async { INDEX_V1_CSV }))
+            .route(
+                "/someone/face/abc/manifest.json",
+                get(|| async { MANIFEST_V1 }),
+            );
+        let base = serve(router).await;
+        let provider = add_official_v1_provider(GitHubCdn::Raw, Arc::new(MemoryCacheStore::new()))
+            .await
+            .unwrap();
+        let cfg = serde_json::json!({
+            "repo_owner": "o",
+            "repo_name": "r",
+            "repo_ref": "main",
+            "raw_base_url": base,
+        });
+        provider.refresh(&cfg.to_string()).await.unwrap();
+
+        let registered = get_official_v1_provider().await.unwrap();
+        assert!(Arc::ptr_eq(&registered, &provider));
+
+        let manifest = provider.get_item_manifest("f1".into()).await.unwrap();
+        assert!(manifest.legacy);
+        assert_eq!(manifest.item.id, "f1");
+        assert_eq!(manifest.item.restype, ResourceTypeV2::WatchFace);
+        assert!(manifest.downloads.contains_key("xmb10"));
+        assert!(
+            provider
+                .inner()
+                .get_validated_manifest("f1".into())
+                .await
+                .is_ok()
+        );
+    }
+
+    fn refresh_cfg(base: &str) -> String {
+        serde_json::json!({
+            "repo_owner": "o",
+            "repo_name": "r",
+            "repo_ref": "main",
+            "raw_base_url": base,
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn offline_refresh_does_not_fetch() {
+        let _registry = isolated_registry().await;
+        let base =
+            serve(Router::new().route("/o/r/main/index.csv", get(|| async { INDEX_V1_CSV }))).await;
+        let provider = OfficialV1Provider::new(GitHubCdn::Raw, Arc::new(MemoryCacheStore::new()));
+        provider.inner().set_offline(true);
+
+        let err = provider.refresh(&refresh_cfg(&base)).await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ProviderError>(),
+            Some(ProviderError::Offline)
+        ));
+        assert!(!provider.inner().has_item("f1"));
+    }
+
+    #[tokio::test]
+    async fn repeated_refresh_hits_cooldown_unless_forced() {
+        let _registry = isolated_registry().await;
+        let base =
+            serve(Router::new().route("/o/r/main/index.csv", get(|| async { INDEX_V1_CSV }))).await;
+        let provider = OfficialV1Provider::new(GitHubCdn::Raw, Arc::new(MemoryCacheStore::new()));
+        provider.refresh(&refresh_cfg(&base)).await.unwrap();
+
+        let err = provider.refresh(&refresh_cfg(&base)).await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ProviderError>(),
+            Some(ProviderError::Cooldown(_))
+        ));
+        assert!(matches!(provider.state(), ProviderState::Ready));
+
+        provider
+            .refresh_with(&refresh_cfg(&base), true)
+            .await
+            .unwrap();
+        assert!(provider.inner().has_item("f1"));
+    }
+
+    // 导出的配置应用到新实例后，按该配置刷新得到同一份索引
+    #[tokio::test]
+    async fn config_round_trips_through_export_and_apply() {
+        let _registry = isolated_registry().await;
+        let base =
+            serve(Router::new().route("/o/r/main/v1.csv", get(|| async { INDEX_V1_CSV }))).await;
+        let provider = OfficialV1Provider::new(GitHubCdn::Raw, Arc::new(MemoryCacheStore::new()));
+        let cfg = serde_json::json!({
+            "repo_owner": "o",
+            "repo_name": "r",
+            "repo_ref": "main",
+            "raw_base_url": base,
+            "index_path": "v1.csv",
+        });
+        provider.refresh(&cfg.to_string()).await.unwrap();
+
+        let exported = provider.export_config();
+        assert_eq!(exported["repo_owner"], "o");
+        assert_eq!(exported["raw_base_url"], base.as_str());
+        assert_eq!(exported["index_path"], "v1.csv");
+
+        let restored = OfficialV1Provider::new(GitHubCdn::Raw, Arc::new(MemoryCacheStore::new()));
+        restored.apply_config(exported.clone()).unwrap();
+        assert_eq!(restored.export_config(), exported);
+        restored.refresh(&exported.to_string()).await.unwrap();
+        assert!(restored.inner().has_item("f1"));
+    }
+}
@@ -1,51 +1,175 @@
 use std::{
     cmp,
-    collections::HashMap,
+    collections::{BTreeMap, HashMap, HashSet},
+    ops::Bound,
     path::PathBuf,
-    sync::{Arc, Mutex},
+    sync::{
+        Arc, Mutex, Weak,
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+    },
     time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use crate::{
+    cache::{CacheStore, FsCacheStore},
     cdn::GitHubCdn,
     community::{
         CommunityProvider,
+        error::{MirrorFailure, MirrorFailures, ProviderError},
         models::{
             common::{
-                ManifestDownloadV2, ManifestItemV2, ManifestV2, PaidTypeV2, ProgressData,
-                ProviderState, ResourceTypeV2, SearchConfig, SortRuleV2,
+                CachedItem, CursorPage, InstalledRef, KeywordMode, ManifestDownloadV2,
+                ManifestItemV2, ManifestV2, MatchField, Page, PageResult, PaidTypeV2,
+                PrefetchReport, PreviewMediaKind, PreviewMediaV2, ProgressData,
+                ProviderCapabilities, ProviderState, ResourceTypeV2, SUPPORTED_MANIFEST_SCHEMA,
+                SearchConfig, SearchHit, SearchMatch, SortOrder, SortRuleV2, TagInfo,
+                UpdateAvailable, UpdateInfo, UpdateStatus, VerifyStatus,
+            },
+            official::{
+                DebugDump, DeviceChipV2, DeviceMapV2, DeviceV2, INDEX_V2_REQUIRED_COLUMNS, IndexV2,
+                ProviderSnapshot, RefreshInfo, RefreshReport, SkippedIndexRow,
             },
-            official::{DeviceMapV2, DeviceV2, IndexV2},
         },
+        validate::validate_manifest,
     },
+    download::{self, DownloadOptions, sanitize_local_filename},
 };
 use account::AccountStore;
 use anyhow::{Context, anyhow};
 use arc_swap::ArcSwap;
-use base64::Engine as _;
 use async_trait::async_trait;
+use base64::Engine as _;
 use futures_util::StreamExt;
-use rand::seq::SliceRandom;
+use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
 use regex::Regex;
 use reqwest::StatusCode;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use tauri::{AppHandle, Manager};
 use tokio::{
     fs::{self, File},
-    io::AsyncWriteExt,
+    io::{AsyncReadExt, AsyncWrite},
+    sync::watch,
 };
+use tracing::Instrument;
 
-const HIDE_PAID: &str = "hide_paid"; // 隐藏付费
-const HIDE_FORCE_PAID: &str = "hide_force_paid"; // 隐藏强制付费
-const QUICK_APP: &str = "quick_app"; // 快应用
-const WATCHFACE: &str = "watchface"; // 表盘
+pub const PROVIDER_NAME: &str = "OfficialV2";
+
+pub(crate) const HIDE_PAID: &str = "hide_paid"; // 隐藏付费
+pub(crate) const HIDE_FORCE_PAID: &str = "hide_force_paid"; // 隐藏强制付费
+pub(crate) const QUICK_APP: &str = "quick_app"; // 快应用
+pub(crate) const WATCHFACE: &str = "watchface"; // 表盘
 const ACCOUNT_SOURCE_STORAGE_KEY: &str = "network_account_source_cfg";
 const ASTROBOX_ACCOUNT_PROVIDER: &str = "astrobox";
+const MAX_DOWNLOAD_REDIRECTS: usize = 8;
+const DEFAULT_WRITE_BUFFER_SIZE: usize = 256 * 1024; // 下载落盘的写缓冲，减少小分块时的系统调用
+const AUTO_REFRESH_MAX_BACKOFF: Duration = Duration::from_secs(6 * 60 * 60); // 连续失败时退避上限
+const DEFAULT_REPO_OWNER: &str = "AstralSightStudios";
+const DEFAULT_REPO_NAME: &str = "AstroBox-Repo";
+const DEFAULT_REPO_REF: &str = "refs/heads/main";
+const DEFAULT_RAW_BASE: &str = "https://raw.githubusercontent.com";
+const STATE_INDEX_FILE: &str = "index_v2.csv"; // 离线缓存文件，位于缓存存储的 state/ 下
+const STATE_DEVICES_FILE: &str = "devices_v2.json";
+const STATE_EXPLORE_FILE: &str = "explore_v2.json";
+const STATE_VALIDATORS_FILE: &str = "validators.json"; // 各资源 URL 的 ETag / Last-Modified
+const STATE_REFRESH_INFO_FILE: &str = "refresh_info.json";
+const MANIFEST_CACHE_DIR: &str = "manifests"; // 磁盘 manifest 缓存：{owner}_{repo}_{commit}.json
+const BLOB_CACHE_DIR: &str = "blobs"; // 按 sha256 寻址的共享产物：blobs/{sha256}
+const STATE_FIRST_SEEN_FILE: &str = "first_seen.json"; // 资源 id -> 首次出现在索引中的时间
 
 // 选中官方镜像源时，图片经境内 CDN 取回后内联为 base64 data URI（绕开 webview 直连 GitHub）
 const MAX_INLINE_IMAGE_BYTES: usize = 4 * 1024 * 1024; // 单张内联上限，超过则回退原始 URL
 const IMAGE_B64_CACHE_CAP: usize = 1024; // 内存缓存条数上限；内容按 commit 寻址、不可变
 const IMAGE_INLINE_CONCURRENCY: usize = 12; // 单页内联的并发抓取数
+const MANIFEST_FETCH_CONCURRENCY: usize = 8; // 批量拉取 manifest（检查更新、预取）的并发数
+const MANIFEST_CACHE_CAP: usize = 512; // manifest 内存缓存条数上限；按 commit 寻址、不可变
+const MANIFEST_FETCH_ATTEMPTS: u32 = 3; // 每个镜像上拉取 manifest 的最多尝试次数（仅网络错误重试）
+const MANIFEST_RETRY_DELAY: Duration = Duration::from_millis(500); // 第 n 次重试前等待 n 倍
+const DEFAULT_MANIFEST_MAX_BYTES: usize = 2 * 1024 * 1024; // 单个 manifest 响应体上限
+const DEFAULT_MANIFEST_TIMEOUT: Duration = Duration::from_secs(20); // 单次 manifest 请求（含读取）超时
+const DEFAULT_REFRESH_COOLDOWN: Duration = Duration::from_secs(30); // 相同配置两次 refresh 的最小间隔
+
+// refresh 传入的配置，缺省字段沿用官方仓库；同时是 export_config / apply_config 的格式
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+struct RefreshConfig {
+    #[serde(default)]
+    cdn: Option<GitHubCdn>,
+    #[serde(default)]
+    repo_owner: Option<String>,
+    #[serde(default)]
+    repo_name: Option<String>,
+    #[serde(default)]
+    repo_ref: Option<String>,
+    // 路径结构与 raw.githubusercontent.com 相同的自建源，缺省为 GitHub
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    raw_base_url: Option<String>,
+    #[serde(default)]
+    preferred_language: Option<String>,
+    #[serde(default)]
+    verify_checksums: bool, // 校验索引/设备表/探索页与仓库中 `<文件>.sha256` 是否一致
+}
+
+impl RefreshConfig {
+    // 空字符串视为未提供配置；无法解析时报错，而不是悄悄退回默认仓库
+    fn parse(cfg: &str) -> anyhow::Result<Self> {
+        if cfg.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        serde_json::from_str(cfg).with_context(|| format!("invalid refresh config: {cfg}"))
+    }
+}
+
+// 索引仓库来源（可指向 fork / 测试仓库）
+#[derive(Debug, Clone)]
+struct RepoSource {
+    owner: String,
+    name: String,
+    reference: String,
+    raw_base: String, // 不带结尾 `/`
+}
+
+impl Default for RepoSource {
+    fn default() -> Self {
+        Self {
+            owner: DEFAULT_REPO_OWNER.to_string(),
+            name: DEFAULT_REPO_NAME.to_string(),
+            reference: DEFAULT_REPO_REF.to_string(),
+            raw_base: DEFAULT_RAW_BASE.to_string(),
+        }
+    }
+}
+
+impl RepoSource {
+    fn from_config(cfg: &RefreshConfig) -> Self {
+        let pick = |value: &Option<String>, default: &str| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .unwrap_or(default)
+                .to_string()
+        };
+        Self {
+            owner: pick(&cfg.repo_owner, DEFAULT_REPO_OWNER),
+            name: pick(&cfg.repo_name, DEFAULT_REPO_NAME),
+            reference: pick(&cfg.repo_ref, DEFAULT_REPO_REF),
+            raw_base: pick(&cfg.raw_base_url, DEFAULT_RAW_BASE)
+                .trim_end_matches('/')
+                .to_string(),
+        }
+    }
+
+    fn raw_url(&self, path: &str) -> String {
+        format!(
+            "{}/{}/{}/{}/{}",
+            self.raw_base,
+            self.owner,
+            self.name,
+            self.reference.trim_matches('/'),
+            path.trim_start_matches('/')
+        )
+    }
+}
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -123,6 +247,176 @@ struct SourceCdnImagesResponse {
     results: Vec<SourceCdnImagesResultItem>,
 }
 
+// 下载遇到重定向时的处理方式（仅在选用镜像源时生效）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize, Serialize)]
+pub enum CdnRedirectPolicy {
+    Follow,        // 照常跟随
+    DenyCrossHost, // 拒绝跨域名重定向
+    #[default]
+    ReapplyCdn, // 对重定向目标重新套用所选 CDN
+}
+
+// 条件请求所需的缓存校验头
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+struct HttpValidators {
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+// 刷新阶段的进度回调（目前仅索引 CSV 下载），可跨任务共享
+pub type RefreshProgressCallback = Arc<dyn Fn(ProgressData) + Send + Sync>;
+
+// get_page_cursor 的游标内容，对外以 base64url(JSON) 形式传递、视为不透明字符串
+#[derive(Debug, Deserialize, Serialize)]
+struct PageCursor {
+    seed: u64,
+    after: String, // 上一页最后一项的 id
+    offset: usize, // 上一页结束时的位置，after 找不到时使用
+}
+
+impl PageCursor {
+    fn encode(&self) -> String {
+        let json = serde_json::to_vec(self).unwrap_or_default();
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(json)
+    }
+
+    fn decode(cursor: &str) -> anyhow::Result<Self> {
+        base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(cursor)
+            .ok()
+            .and_then(|json| serde_json::from_slice(&json).ok())
+            .ok_or_else(|| anyhow!("invalid page cursor `{cursor}`"))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ManifestLimits {
+    max_bytes: usize,
+    timeout: Duration,
+}
+
+impl Default for ManifestLimits {
+    fn default() -> Self {
+        Self {
+            max_bytes: DEFAULT_MANIFEST_MAX_BYTES,
+            timeout: DEFAULT_MANIFEST_TIMEOUT,
+        }
+    }
+}
+
+struct FetchedResource {
+    body: Vec<u8>,
+    validators: HttpValidators,
+}
+
+// 一次下载命中的产物
+struct ResolvedArtifact {
+    item: IndexV2,
+    device: String, // 实际命中的 downloads 键（可能回退到 default）
+    entry: ManifestDownloadV2,
+    file_name: String,
+}
+
+// 一次下载的解析结果
+struct ResolvedDownload {
+    item: IndexV2,
+    entry: ManifestDownloadV2,
+    file_name: String,
+    url: String,
+    raw_url: String, // 未经 CDN 转换的原始地址，供镜像回退链重新转换
+}
+
+type ManifestKey = (String, String, String); // (owner, repo, commit_hash)
+
+// 关键词倒排索引：名称/标签按空白切出的小写词，连同它的每个后缀 -> 索引下标，按键有序存放。
+// 不含空白的搜索词命中名称或标签的子串必然落在某个词内，也就是某个后缀的前缀，
+// 因此在有序表上做一次前缀范围查找即可得到与逐行匹配一致的结果，无需扫描词表；短语见 term_hits
+#[derive(Default)]
+struct SearchIndex {
+    suffixes: BTreeMap<String, Vec<usize>>,
+}
+
+impl SearchIndex {
+    fn build(list: &[IndexV2]) -> Self {
+        let mut suffixes: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for (pos, item) in list.iter().enumerate() {
+            let words = item
+                .name
+                .split_whitespace()
+                .chain(item.tags.iter().flat_map(|tag| tag.split_whitespace()));
+            for word in words {
+                let word = word.to_lowercase();
+                for (start, _) in word.char_indices() {
+                    let posting = suffixes.entry(word[start..].to_string()).or_default();
+                    if posting.last() != Some(&pos) {
+                        posting.push(pos);
+                    }
+                }
+            }
+        }
+        Self { suffixes }
+    }
+
+    // 名称或标签中含有该词（须已小写、不含空白）的下标
+    fn substring_hits(&self, term: &str) -> HashSet<usize> {
+        self.suffixes
+            .range::<str, _>((Bound::Included(term), Bound::Unbounded))
+            .take_while(|(suffix, _)| suffix.starts_with(term))
+            .flat_map(|(_, posting)| posting.iter().copied())
+            .collect()
+    }
+
+    // 每个词（须已小写）至少命中一个索引词，返回同时满足所有词的下标
+    fn lookup(&self, terms: &[String]) -> HashSet<usize> {
+        let mut result: Option<HashSet<usize>> = None;
+        for term in terms {
+            let hits = self.substring_hits(term);
+            let next = match result {
+                Some(acc) => acc.intersection(&hits).copied().collect(),
+                None => hits,
+            };
+            if next.is_empty() {
+                return next;
+            }
+            result = Some(next);
+        }
+        result.unwrap_or_default()
+    }
+
+    // 单个搜索词的命中下标。短语的每一段都必然命中某个索引词，
+    // 先用倒排索引取候选，再逐行确认短语整体出现在名称或同一标签中
+    fn term_hits(&self, term: &str, list: &[IndexV2]) -> HashSet<usize> {
+        let pieces = term
+            .split_whitespace()
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        let mut hits = self.lookup(&pieces);
+        if pieces.len() > 1 {
+            hits.retain(|pos| {
+                list.get(*pos)
+                    .is_some_and(|item| item_matches_term(item, term))
+            });
+        }
+        hits
+    }
+}
+
+// 同一代的索引数据，随 refresh 整体替换；读取方一次 load 即得到彼此一致的列表、id 表与倒排索引
+#[derive(Default)]
+struct IndexSnapshot {
+    list: Vec<IndexV2>,
+    by_id: HashMap<String, usize>,      // id -> list 下标
+    name_keys: HashMap<String, String>, // id -> 名称排序键
+    search: SearchIndex,
+}
+
+// 自动补全候选：名称或标签，key 为小写形式，按 key 排序存放
+struct SuggestEntry {
+    key: String,
+    text: String,
+    weight: u64, // 同名资源数或标签下的资源数
+}
+
 // 一次图片内联请求：定位某资源仓内某相对图片
 struct ImageRef {
     id: String,
@@ -134,7 +428,11 @@ struct ImageRef {
 
 // content-type 缺失时按 URL 扩展名兜底推断图片 MIME
 fn guess_image_mime(url: &str) -> &'static str {
-    let path = url.split(['?', '#']).next().unwrap_or(url).to_ascii_lowercase();
+    let path = url
+        .split(['?', '#'])
+        .next()
+        .unwrap_or(url)
+        .to_ascii_lowercase();
     if path.ends_with(".png") {
         "image/png"
     } else if path.ends_with(".jpg") || path.ends_with(".jpeg") {
@@ -156,45 +454,292 @@ fn guess_image_mime(url: &str) -> &'static str {
 
 pub struct OfficialV2Provider {
     cdn: ArcSwap<GitHubCdn>,
-    app_handle: AppHandle,
-    index: ArcSwap<Vec<IndexV2>>,
-    splited_index: ArcSwap<Vec<Vec<IndexV2>>>,
-    splited_limit: ArcSwap<usize>,
+    redirect_policy: ArcSwap<CdnRedirectPolicy>,
+    repo: ArcSwap<RepoSource>,
+    app_handle: Option<AppHandle>, // 仅用于读取账号与本地配置，脱离 Tauri 时为 None
+    client: Option<reqwest::Client>, // 注入的 HTTP 客户端；None 时每次请求按全局配置构建
+    store: Arc<dyn CacheStore>,    // 离线状态与下载产物的存储后端
+    index: ArcSwap<IndexSnapshot>,
+    session_seed: ArcSwap<u64>, // 随机排序种子，refresh 不变，仅 reshuffle 时重新生成
     device_map: ArcSwap<DeviceMapV2>,
     explore: ArcSwap<serde_json::Value>,
-    state: ArcSwap<ProviderState>,
+    state: watch::Sender<ProviderState>, // 当前状态，同时推送给 subscribe_state 的订阅者
     placeholder_index: ArcSwap<u32>,
     // 图片 base64 内联缓存：cosKey -> data URI（commit 寻址、不可变）
     image_b64_cache: Mutex<HashMap<String, Arc<str>>>,
+    // manifest 内存缓存：(owner, repo, commit) -> manifest，refresh 时剔除已不在索引中的条目
+    manifest_cache: Mutex<HashMap<ManifestKey, Arc<ManifestV2>>>,
+    // 条件刷新校验头：资源 URL -> ETag / Last-Modified
+    validators: Mutex<HashMap<String, HttpValidators>>,
+    tags: ArcSwap<Vec<TagInfo>>,             // 随 index 一同重算
+    suggestions: ArcSwap<Vec<SuggestEntry>>, // 随 index 一同重算
+    refresh_report: ArcSwap<RefreshReport>,
+    refresh_info: ArcSwap<RefreshInfo>,
+    first_seen: ArcSwap<HashMap<String, u64>>, // 毫秒时间戳，0 表示首次同步时已存在
+    refreshing: AtomicBool,
+    offline: AtomicBool, // 离线模式：只读磁盘缓存，不发起任何网络请求
+    write_buffer_size: AtomicUsize,
+    strict_compatibility: AtomicBool, // 产物与设备不兼容时报错而非仅记录警告
+    dedup_blobs: AtomicBool,          // 相同 sha256 的产物在资源间共享一份
+    verify_checksums: AtomicBool,     // 随 refresh 配置更新
+    manifest_limits: ArcSwap<ManifestLimits>,
+    preferred_language: ArcSwap<Option<String>>, // 详情页/列表展示文本使用的语言
+    last_refresh_cfg: ArcSwap<RefreshConfig>,    // 自动刷新沿用最近一次 refresh 的配置
+    last_refresh_started: Mutex<Option<Instant>>, // 最近一次实际发起 refresh 的时间，用于冷却
+    refresh_cooldown: ArcSwap<Duration>,
+    refresh_progress: ArcSwap<Option<RefreshProgressCallback>>,
+    mirror_chain: ArcSwap<Vec<GitHubCdn>>, // 下载失败时依次尝试的镜像
+    auto_refresh: Mutex<Option<tauri::async_runtime::JoinHandle<()>>>,
 }
 
 impl OfficialV2Provider {
-    pub fn new(cdn: GitHubCdn, app_handle: AppHandle) -> Self {
+    // 不依赖 Tauri 的构造方式，官方镜像源等需要账号的功能不可用
+    pub fn new(cdn: GitHubCdn, store: Arc<dyn CacheStore>) -> Self {
         Self {
             cdn: ArcSwap::new(Arc::new(cdn)),
-            app_handle,
-            index: ArcSwap::new(Arc::new(Vec::new())),
-            splited_index: ArcSwap::new(Arc::new(Vec::new())),
-            splited_limit: ArcSwap::new(Arc::new(0)),
+            redirect_policy: ArcSwap::new(Arc::new(CdnRedirectPolicy::default())),
+            repo: ArcSwap::new(Arc::new(RepoSource::default())),
+            app_handle: None,
+            client: None,
+            store,
+            index: ArcSwap::new(Arc::new(IndexSnapshot::default())),
+            session_seed: ArcSwap::new(Arc::new(rand::random())),
             device_map: ArcSwap::new(Arc::new(DeviceMapV2::default())),
             explore: ArcSwap::new(Arc::new(serde_json::Value::Null)),
-            state: ArcSwap::new(Arc::new(ProviderState::Updating)),
+            state: watch::Sender::new(ProviderState::Updating),
             placeholder_index: ArcSwap::new(Arc::new(0)),
             image_b64_cache: Mutex::new(HashMap::new()),
+            manifest_cache: Mutex::new(HashMap::new()),
+            validators: Mutex::new(HashMap::new()),
+            tags: ArcSwap::new(Arc::new(Vec::new())),
+            suggestions: ArcSwap::new(Arc::new(Vec::new())),
+            refresh_report: ArcSwap::new(Arc::new(RefreshReport::default())),
+            refresh_info: ArcSwap::new(Arc::new(RefreshInfo::default())),
+            first_seen: ArcSwap::new(Arc::new(HashMap::new())),
+            refreshing: AtomicBool::new(false),
+            offline: AtomicBool::new(false),
+            write_buffer_size: AtomicUsize::new(DEFAULT_WRITE_BUFFER_SIZE),
+            strict_compatibility: AtomicBool::new(false),
+            dedup_blobs: AtomicBool::new(false),
+            verify_checksums: AtomicBool::new(false),
+            manifest_limits: ArcSwap::new(Arc::new(ManifestLimits::default())),
+            preferred_language: ArcSwap::new(Arc::new(None)),
+            last_refresh_cfg: ArcSwap::new(Arc::new(RefreshConfig::default())),
+            last_refresh_started: Mutex::new(None),
+            refresh_cooldown: ArcSwap::new(Arc::new(DEFAULT_REFRESH_COOLDOWN)),
+            refresh_progress: ArcSwap::new(Arc::new(None)),
+            mirror_chain: ArcSwap::new(Arc::new(Vec::new())),
+            auto_refresh: Mutex::new(None),
+        }
+    }
+
+    // 缓存位于 app_cache_dir/community/official_v2；取不到时回退到系统临时目录
+    pub fn from_app_handle(cdn: GitHubCdn, app_handle: AppHandle) -> Self {
+        Self::from_app_handle_with_fallback(cdn, app_handle, default_fallback_cache_root())
+    }
+
+    // fallback_root 为 app_cache_dir 不可用（无头/精简环境）时使用的缓存根目录
+    pub fn from_app_handle_with_fallback(
+        cdn: GitHubCdn,
+        app_handle: AppHandle,
+        fallback_root: PathBuf,
+    ) -> Self {
+        let cache_dir = match app_handle.path().app_cache_dir() {
+            Ok(dir) => dir,
+            Err(err) => {
+                log::warn!(
+                    "[OfficialV2] app cache directory unavailable ({err}), falling back to {}",
+                    fallback_root.display()
+                );
+                fallback_root
+            }
+        };
+        let root = cache_dir.join("community").join("official_v2");
+        let mut provider = Self::new(cdn, Arc::new(FsCacheStore::new(root)));
+        provider.app_handle = Some(app_handle);
+        provider
+    }
+
+    // 使用调用方提供的客户端（共享连接池、测试中指向 mock 服务器等）。
+    // 下载的重定向策略需要逐跳处理，客户端应以 redirect::Policy::none() 构建；
+    // 自行跟随重定向的客户端落到违反策略的地址时下载报错
+    pub fn with_client(
+        cdn: GitHubCdn,
+        store: Arc<dyn CacheStore>,
+        client: reqwest::Client,
+    ) -> Self {
+        let mut provider = Self::new(cdn, store);
+        provider.client = Some(client);
+        provider
+    }
+
+    pub(crate) fn http_client(&self) -> reqwest::Client {
+        match &self.client {
+            Some(client) => client.clone(),
+            None => crate::net::default_client(),
         }
     }
 
+    fn app_handle(&self) -> anyhow::Result<&AppHandle> {
+        self.app_handle
+            .as_ref()
+            .ok_or_else(|| anyhow!("AppHandle unavailable for this provider"))
+    }
+
     pub fn set_cdn(&self, cdn: GitHubCdn) {
         self.cdn.store(Arc::new(cdn));
     }
 
-    fn cache_root(&self) -> anyhow::Result<PathBuf> {
-        let base = self
-            .app_handle
-            .path()
-            .app_cache_dir()
-            .map_err(|err| anyhow!("app cache directory unavailable: {err}"))?;
-        Ok(base.join("community").join("official_v2"))
+    // 离线模式下 refresh 只从磁盘缓存加载，manifest/下载等请求直接返回 ProviderError::Offline
+    pub fn set_offline(&self, offline: bool) {
+        self.offline.store(offline, Ordering::SeqCst);
+    }
+
+    pub fn is_offline(&self) -> bool {
+        self.offline.load(Ordering::SeqCst)
+    }
+
+    pub(crate) fn ensure_online(&self) -> anyhow::Result<()> {
+        if self.is_offline() {
+            return Err(ProviderError::Offline.into());
+        }
+        Ok(())
+    }
+
+    pub(crate) fn set_state(&self, state: ProviderState) {
+        self.state.send_replace(state);
+    }
+
+    // 下载写入文件时的缓冲区大小，0 表示不缓冲
+    pub fn set_write_buffer_size(&self, size: usize) {
+        self.write_buffer_size.store(size, Ordering::SeqCst);
+    }
+
+    // 开启后，manifest 提供 sha256 的产物额外存入 blobs/，其他资源下载同一文件时直接链接/复制，不再联网
+    pub fn set_dedup_blobs(&self, enabled: bool) {
+        self.dedup_blobs.store(enabled, Ordering::SeqCst);
+    }
+
+    // 相同配置的 refresh 在冷却时间内不联网，返回 ProviderError::Cooldown；Duration::ZERO 关闭冷却
+    pub fn set_refresh_cooldown(&self, cooldown: Duration) {
+        self.refresh_cooldown.store(Arc::new(cooldown));
+    }
+
+    // 记录实际发起 refresh 的时间，冷却从此刻开始计算
+    pub(crate) fn mark_refresh_started(&self) {
+        if let Ok(mut slot) = self.last_refresh_started.lock() {
+            *slot = Some(Instant::now());
+        }
+    }
+
+    // 距离下次可实际刷新的剩余时间，供界面禁用刷新按钮；为零表示可立即刷新
+    pub fn refresh_cooldown_remaining(&self) -> Duration {
+        let started = self.last_refresh_started.lock().ok().and_then(|slot| *slot);
+        match started {
+            Some(started) => self
+                .refresh_cooldown
+                .load()
+                .saturating_sub(started.elapsed()),
+            None => Duration::ZERO,
+        }
+    }
+
+    // manifest 响应体上限与单次请求超时，防止异常仓库拖垮内存或一直挂起
+    pub fn set_manifest_limits(&self, max_bytes: usize, timeout: Duration) {
+        self.manifest_limits.store(Arc::new(ManifestLimits {
+            max_bytes: max_bytes.max(1),
+            timeout,
+        }));
+    }
+
+    pub fn set_strict_compatibility(&self, strict: bool) {
+        self.strict_compatibility.store(strict, Ordering::SeqCst);
+    }
+
+    // 索引下载进度：已知长度时 progress 为 0~1；长度未知时 progress 恒为 0、status 为 "indeterminate"
+    pub fn set_refresh_progress_callback(&self, cb: Option<RefreshProgressCallback>) {
+        self.refresh_progress.store(Arc::new(cb));
+    }
+
+    // None 表示直接使用 manifest 的基础字段
+    pub fn set_preferred_language(&self, lang: Option<String>) {
+        let lang = lang.filter(|lang| !lang.trim().is_empty());
+        self.preferred_language.store(Arc::new(lang));
+    }
+
+    // 当前 CDN 下载失败时按顺序改用这些镜像重试；为空时不回退
+    pub fn set_mirror_chain(&self, chain: Vec<GitHubCdn>) {
+        self.mirror_chain.store(Arc::new(chain));
+    }
+
+    pub fn set_redirect_policy(&self, policy: CdnRedirectPolicy) {
+        self.redirect_policy.store(Arc::new(policy));
+    }
+
+    // 后台定时刷新。任务只持有 Weak 引用，provider 从注册表移除并释放后任务自行退出；
+    // 手动刷新进行中时跳过本轮，连续失败时间隔翻倍直至上限
+    pub fn start_auto_refresh(self: Arc<Self>, interval: Duration) {
+        self.stop_auto_refresh();
+
+        let weak: Weak<Self> = Arc::downgrade(&self);
+        let handle = tauri::async_runtime::spawn(async move {
+            let mut delay = interval;
+            loop {
+                tokio::time::sleep(delay).await;
+                let Some(provider) = weak.upgrade() else {
+                    break;
+                };
+
+                if provider.refreshing.load(Ordering::SeqCst) {
+                    log::info!("[OfficialV2] refresh in progress, skipping auto refresh");
+                    continue;
+                }
+
+                let cfg = provider.last_refresh_cfg.load_full();
+                match provider.refresh_parsed(&cfg, false).await {
+                    Ok(()) => delay = interval,
+                    // 刚有过手动刷新，本轮视为成功
+                    Err(err)
+                        if matches!(
+                            err.downcast_ref::<ProviderError>(),
+                            Some(ProviderError::Cooldown(_))
+                        ) =>
+                    {
+                        delay = interval
+                    }
+                    Err(err) => {
+                        delay = cmp::min(delay * 2, cmp::max(interval, AUTO_REFRESH_MAX_BACKOFF));
+                        log::warn!(
+                            "[OfficialV2] auto refresh failed, retry in {:?}: {err}",
+                            delay
+                        );
+                    }
+                }
+            }
+        });
+
+        if let Ok(mut slot) = self.auto_refresh.lock() {
+            *slot = Some(handle);
+        }
+    }
+
+    pub fn stop_auto_refresh(&self) {
+        if let Some(handle) = self
+            .auto_refresh
+            .lock()
+            .ok()
+            .and_then(|mut slot| slot.take())
+        {
+            handle.abort();
+        }
+    }
+
+    // 下载产物以文件形式交给调用方，存储后端必须能提供本地路径
+    fn artifact_path(&self, item_id: &str, file_name: &str) -> anyhow::Result<PathBuf> {
+        let key = format!("{}/{}", item_id, sanitize_local_filename(file_name));
+        self.store
+            .local_path(&key)
+            .ok_or_else(|| anyhow!("cache store does not provide local files"))
     }
 
     pub fn device_map(&self) -> Arc<DeviceMapV2> {
@@ -220,26 +765,145 @@ impl OfficialV2Provider {
         all
     }
 
+    // fetch 为 true 表示应用当前实际支持、会为其拉取资源的设备；
+    // 其余设备仅在设备表中登记。需要全部设备请用 device_map_all()
+    pub fn fetchable_devices(&self) -> Vec<DeviceV2> {
+        self.device_map_all()
+            .into_iter()
+            .filter(|dev| dev.fetch)
+            .collect()
+    }
+
+    // 固件/字体的兼容性往往跟随芯片平台而非具体型号
+    pub fn devices_by_chip(&self, chip: DeviceChipV2) -> Vec<DeviceV2> {
+        self.device_map_all()
+            .into_iter()
+            .filter(|dev| dev.chip == chip)
+            .collect()
+    }
+
     pub fn explore(&self) -> Arc<serde_json::Value> {
         self.explore.load().clone()
     }
 
     pub fn device_map_id_to_name(&self, id: &str) -> Option<String> {
-        for dev in self.device_map_all() {
-            if dev.id == id {
-                return Some(dev.name.clone());
-            }
-        }
-        None
+        let device_map = self.device_map.load();
+        device_map
+            .xiaomi
+            .values()
+            .chain(device_map.vivo.values())
+            .find(|dev| dev.id == id)
+            .map(|dev| dev.name.clone())
     }
 
     pub fn device_map_name_to_id(&self, name: &str) -> Option<String> {
-        for dev in self.device_map_all() {
-            if dev.name == name {
-                return Some(dev.id.clone());
+        let device_map = self.device_map.load();
+        device_map
+            .xiaomi
+            .values()
+            .chain(device_map.vivo.values())
+            .find(|dev| dev.name == name)
+            .map(|dev| dev.id.clone())
+    }
+
+    fn device_by_id(&self, id: &str) -> Option<(&'static str, DeviceV2)> {
+        let device_map = self.device_map.load();
+        device_map
+            .xiaomi
+            .values()
+            .map(|dev| ("xiaomi", dev))
+            .chain(device_map.vivo.values().map(|dev| ("vivo", dev)))
+            .find(|(_, dev)| dev.id == id)
+            .map(|(vendor, dev)| (vendor, dev.clone()))
+    }
+
+    pub fn is_compatible(&self, item: &IndexV2, device: &DeviceV2) -> bool {
+        self.check_compatibility(item, device).is_ok()
+    }
+
+    // 依次检查厂商、设备型号与芯片平台，不兼容时返回原因。
+    // 固件/字体包跟随芯片平台，未列出的型号只要芯片一致也视为兼容
+    pub fn check_compatibility(&self, item: &IndexV2, device: &DeviceV2) -> Result<(), String> {
+        let vendor = self.device_by_id(&device.id).map(|(vendor, _)| vendor);
+        if let Some(vendor) = vendor
+            && !item.device_vendors.is_empty()
+            && !item
+                .device_vendors
+                .iter()
+                .any(|v| v.eq_ignore_ascii_case(vendor))
+        {
+            return Err(format!(
+                "{} 仅支持 {} 设备，{} 属于 {vendor}",
+                item.name,
+                item.device_vendors.join("/"),
+                device.name
+            ));
+        }
+
+        if item.devices.is_empty() || item.devices.iter().any(|d| d == &device.id) {
+            return Ok(());
+        }
+
+        // 按首次出现顺序去重，型号列表不保证同芯片相邻
+        let mut chips: Vec<DeviceChipV2> = Vec::new();
+        for chip in item
+            .devices
+            .iter()
+            .filter_map(|id| self.device_by_id(id).map(|(_, dev)| dev.chip))
+        {
+            if !chips.contains(&chip) {
+                chips.push(chip);
             }
         }
-        None
+        let chip_matches = chips.contains(&device.chip);
+        if chip_matches
+            && matches!(
+                item.restype,
+                ResourceTypeV2::Firmware | ResourceTypeV2::FontPack
+            )
+        {
+            return Ok(());
+        }
+        if !chips.is_empty() && !chip_matches {
+            return Err(format!(
+                "{} 面向 {:?} 芯片，{} 使用 {:?} 芯片",
+                item.name, chips, device.name, device.chip
+            ));
+        }
+        Err(format!(
+            "{} 不在 {} 的支持设备列表中",
+            device.name, item.name
+        ))
+    }
+
+    // 经 id 表 O(1) 定位索引项
+    fn index_item(&self, item_id: &str) -> Option<IndexV2> {
+        let index = self.index.load();
+        let pos = *index.by_id.get(item_id)?;
+        index.list.get(pos).cloned()
+    }
+
+    // 解析分享链接等外部引用：先精确匹配 id，再精确匹配名称，最后忽略大小写匹配名称
+    pub fn find_item(&self, query: &str) -> Option<IndexV2> {
+        let query = query.trim();
+        if query.is_empty() {
+            return None;
+        }
+        self.index_item(query).or_else(|| {
+            let index = self.index.load();
+            index
+                .list
+                .iter()
+                .find(|entry| entry.name == query)
+                .or_else(|| {
+                    let query = query.to_lowercase();
+                    index
+                        .list
+                        .iter()
+                        .find(|entry| entry.name.to_lowercase() == query)
+                })
+                .cloned()
+        })
     }
 
     pub fn device_map_model_to_id(&self, model: &str) -> Option<String> {
@@ -253,33 +917,53 @@ impl OfficialV2Provider {
         None
     }
 
-    fn split_index(&self, limit: usize, sort: SortRuleV2) {
-        let index = self.index.load().clone();
-        let mut rng = rand::rng();
-        let mut sorted_index = (*index).clone();
-
+    // 按规则与方向就地排序；Random 忽略方向，按种子确定性打乱
+    // 名称排序键取自与 list 同一代的快照
+    fn sort_index(
+        list: &mut [IndexV2],
+        keys: &HashMap<String, String>,
+        sort: SortRuleV2,
+        order: SortOrder,
+        seed: u64,
+    ) {
         match sort {
-            SortRuleV2::Random => sorted_index.shuffle(&mut rng),
-            SortRuleV2::Name => {
-                sorted_index.sort_by(|a, b| a.name.cmp(&b.name));
+            SortRuleV2::Random => {
+                list.shuffle(&mut StdRng::seed_from_u64(seed));
+                return;
             }
-            SortRuleV2::Time => {
-                sorted_index.reverse();
+            SortRuleV2::Name => {
+                list.sort_by(|a, b| {
+                    let key_a = keys.get(&a.id).map(String::as_str).unwrap_or(&a.name);
+                    let key_b = keys.get(&b.id).map(String::as_str).unwrap_or(&b.name);
+                    key_a.cmp(key_b).then_with(|| a.name.cmp(&b.name))
+                });
             }
+            SortRuleV2::Time => {} // 索引本身按添加时间升序
         };
+        if order == SortOrder::Desc {
+            list.reverse();
+        }
+    }
 
-        let splited_index = sorted_index
-            .chunks(limit)
-            .map(|c| c.to_vec())
-            .collect::<Vec<_>>();
-        self.splited_index.store(Arc::new(splited_index));
-        self.splited_limit.store(Arc::new(limit));
+    // 只替换资源仓库的拉取基址（如 OfficialV1Provider 的自建源），None 恢复为 GitHub
+    pub(crate) fn set_raw_base_url(&self, raw_base_url: Option<&str>) {
+        let mut repo = (*self.repo.load_full()).clone();
+        repo.raw_base = raw_base_url
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .unwrap_or(DEFAULT_RAW_BASE)
+            .trim_end_matches('/')
+            .to_string();
+        self.repo.store(Arc::new(repo));
     }
 
     pub fn build_repo_raw_url(&self, owner: &str, name: &str, commit_hash: &str) -> String {
         format!(
-            "https://raw.githubusercontent.com/{}/{}/{}",
-            owner, name, commit_hash
+            "{}/{}/{}/{}",
+            self.repo.load().raw_base,
+            owner,
+            name,
+            commit_hash
         )
     }
 
@@ -296,51 +980,672 @@ impl OfficialV2Provider {
         )
     }
 
-    fn resolve_repo_asset_url(&self, base: &str, path: &str) -> String {
-        if path.starts_with("http://")
-            || path.starts_with("https://")
-            || path.starts_with("data:")
-            || path.starts_with("blob:")
-            || path.starts_with("tauri:")
-            || path.starts_with('/')
-        {
-            return path.to_string();
+    // 资源仓内的路径（图片、链接图标、下载文件）统一经此解析，列表、详情与下载共用：
+    // 相对路径（含 `./`、`/` 开头）拼到仓库 CDN 基址，
+    // 绝对 URL 原样保留，仅 raw.githubusercontent.com 再经当前 CDN 转换
+    pub fn resolve_repo_asset(&self, item: &IndexV2, path: &str) -> String {
+        (*self.cdn.load_full()).convert_url(&self.resolve_repo_asset_raw(item, path))
+    }
+
+    // 同 resolve_repo_asset，但不套用 CDN
+    fn resolve_repo_asset_raw(&self, item: &IndexV2, path: &str) -> String {
+        let path = path.trim();
+        match repo_relative_path(path) {
+            Some(rel) => format!(
+                "{}/{}",
+                self.build_repo_raw_url(&item.repo_owner, &item.repo_name, &item.repo_commit_hash),
+                rel
+            ),
+            None => path.to_string(),
         }
-        format!(
-            "{}/{}",
-            base.trim_end_matches('/'),
-            path.trim_start_matches('/')
-        )
     }
 
-    async fn current_account_source(&self) -> AccountSourceId {
-        account::local_storage_get_json::<AccountSourceConfig>(
-            &self.app_handle,
-            ACCOUNT_SOURCE_STORAGE_KEY,
-        )
-        .await
-        .ok()
-        .flatten()
-        .and_then(|cfg| cfg.source)
-        .unwrap_or_default()
+    // 拉取远端数据并设置状态：索引失败为 Failed；索引成功但设备表/探索页失败为 Partial
+    async fn refresh_remote(&self, cfg: &RefreshConfig) -> anyhow::Result<()> {
+        //更新cdn
+        let cdn: GitHubCdn = cfg.cdn.unwrap_or(GitHubCdn::Raw);
+        self.cdn.store(Arc::new(cdn));
+        self.repo.store(Arc::new(RepoSource::from_config(cfg)));
+        if cfg.preferred_language.is_some() {
+            self.set_preferred_language(cfg.preferred_language.clone());
+        }
+        self.verify_checksums
+            .store(cfg.verify_checksums, Ordering::SeqCst);
+        let repo = self.repo.load_full();
+        let client = self.http_client();
+
+        if let Err(err) = self.refresh_index(&client, &repo).await {
+            let message = format!("索引加载失败: {err:#}");
+            self.set_state(ProviderState::Failed(message));
+            // 保留原始错误，调用方可 downcast 出 ProviderError::EmptyIndex 等
+            return Err(err.context("索引加载失败"));
+        }
+
+        let mut failures = Vec::new();
+        let mut tampered = None;
+        for (label, result) in [
+            (
+                "设备列表加载失败",
+                self.refresh_device_map(&client, &repo).await,
+            ),
+            ("探索页加载失败", self.refresh_explore(&client, &repo).await),
+        ] {
+            let Err(err) = result else { continue };
+            failures.push(format!("{label}: {err:#}"));
+            // 校验失败说明下载源不可信，整体按失败处理而不是部分可用
+            if tampered.is_none() && is_checksum_mismatch(&err) {
+                tampered = Some(err.context(label));
+            }
+        }
+        if let Some(err) = tampered {
+            self.set_state(ProviderState::Failed(failures.join("; ")));
+            return Err(err);
+        }
+        self.persist_validators().await;
+
+        if failures.is_empty() {
+            self.set_state(ProviderState::Ready);
+            Ok(())
+        } else {
+            let message = failures.join("; ");
+            self.set_state(ProviderState::Partial(message.clone()));
+            Err(anyhow!(message))
+        }
     }
 
-    async fn current_astrobox_token(&self) -> anyhow::Result<String> {
-        let account = AccountStore::new(ASTROBOX_ACCOUNT_PROVIDER)
-            .load(&self.app_handle)
+    // 更新index（未变化时 304，保留内存数据且不重新分片）
+    async fn refresh_index(
+        &self,
+        client: &reqwest::Client,
+        repo: &RepoSource,
+    ) -> anyhow::Result<()> {
+        let url = (*self.cdn.load_full()).convert_url(&repo.raw_url("index_v2.csv"));
+        let has_index = !self.index.load().list.is_empty();
+        let progress_cb = self.refresh_progress.load_full();
+        if let Some(fetched) = self
+            .fetch_if_modified(client, &url, has_index, progress_cb.as_ref().as_ref())
             .await
-            .context("failed to read AstroBox account")?
-            .ok_or_else(|| anyhow!("请先登录 AstroBox 账号"))?;
-        account
-            .token
-            .filter(|token| !token.trim().is_empty())
-            .ok_or_else(|| anyhow!("请先登录 AstroBox 账号"))
+            .with_context(|| format!("failed to fetch {url}"))?
+        {
+            self.verify_checksum(client, repo, "index_v2.csv", &fetched.body)
+                .await?;
+            let sanitized = sanitize_index_csv(&fetched.body);
+            let (list, report) = self.parse_index(&sanitized)?;
+            let skipped = report.skipped_rows.len();
+            self.refresh_report.store(Arc::new(report));
+            // 空索引视为失败，界面据此显示"目录不可用"而非空列表
+            if list.is_empty() {
+                return Err(ProviderError::EmptyIndex(skipped).into());
+            }
+            self.track_first_seen(&list).await;
+            self.store_index(list);
+            self.commit_validators(&url, fetched.validators);
+            self.persist_state_file(STATE_INDEX_FILE, sanitized.as_bytes())
+                .await;
+        }
+        self.record_refresh_info(&url).await;
+        Ok(())
     }
 
-    async fn resolve_source_cdn_download_url(
-        &self,
-        item_id: &str,
-        device: Option<&str>,
+    // 索引没有上架时间，通过与上一份索引比对推断：新出现的 id 记为当前时间。
+    // 首次同步（无任何旧数据）时全部记为 0，避免整个仓库都被当作新上架
+    async fn track_first_seen(&self, list: &[IndexV2]) {
+        let now = unix_millis(SystemTime::now());
+        let prev = self.first_seen.load_full();
+        let current = self.index.load_full();
+        let first_sync = prev.is_empty() && current.by_id.is_empty();
+
+        let seen = list
+            .iter()
+            .map(|item| {
+                let ts = prev
+                    .get(&item.id)
+                    .copied()
+                    // 旧版本缓存没有 first_seen 记录时，以已加载的索引为基线
+                    .or_else(|| {
+                        (prev.is_empty() && current.by_id.contains_key(&item.id)).then_some(0)
+                    })
+                    .unwrap_or(if first_sync { 0 } else { now });
+                (item.id.clone(), ts)
+            })
+            .collect::<HashMap<_, _>>();
+
+        match serde_json::to_vec(&seen) {
+            Ok(data) => self.persist_state_file(STATE_FIRST_SEEN_FILE, &data).await,
+            Err(err) => log::warn!("[OfficialV2] failed to serialize first seen map: {err}"),
+        }
+        self.first_seen.store(Arc::new(seen));
+    }
+
+    // 索引刷新成功（含 304 未变化）后记录新鲜度信息
+    async fn record_refresh_info(&self, index_url: &str) {
+        let upstream_modified = self
+            .validators
+            .lock()
+            .ok()
+            .and_then(|map| map.get(index_url).and_then(|v| v.last_modified.clone()));
+        let info = RefreshInfo {
+            last_refresh_at: Some(unix_millis(SystemTime::now())),
+            item_count: self.index.load().list.len(),
+            upstream_modified,
+        };
+        match serde_json::to_vec(&info) {
+            Ok(data) => {
+                self.persist_state_file(STATE_REFRESH_INFO_FILE, &data)
+                    .await
+            }
+            Err(err) => log::warn!("[OfficialV2] failed to serialize refresh info: {err}"),
+        }
+        self.refresh_info.store(Arc::new(info));
+    }
+
+    // 更新设备map
+    async fn refresh_device_map(
+        &self,
+        client: &reqwest::Client,
+        repo: &RepoSource,
+    ) -> anyhow::Result<()> {
+        let url = (*self.cdn.load_full()).convert_url(&repo.raw_url("devices_v2.json"));
+        let has_devices = {
+            let map = self.device_map.load();
+            !map.xiaomi.is_empty() || !map.vivo.is_empty()
+        };
+        if let Some(fetched) = self
+            .fetch_if_modified(client, &url, has_devices, None)
+            .await
+            .with_context(|| format!("failed to fetch {url}"))?
+        {
+            self.verify_checksum(client, repo, "devices_v2.json", &fetched.body)
+                .await?;
+            let map: DeviceMapV2 = serde_json::from_slice(&fetched.body)
+                .with_context(|| format!("failed to parse {url}"))?;
+            self.device_map.store(Arc::new(map));
+            self.commit_validators(&url, fetched.validators);
+            self.persist_state_file(STATE_DEVICES_FILE, &fetched.body)
+                .await;
+        }
+        Ok(())
+    }
+
+    // 更新探索页
+    async fn refresh_explore(
+        &self,
+        client: &reqwest::Client,
+        repo: &RepoSource,
+    ) -> anyhow::Result<()> {
+        let url = (*self.cdn.load_full()).convert_url(&repo.raw_url("explore_v2.json"));
+        let has_explore = !self.explore.load().is_null();
+        if let Some(fetched) = self
+            .fetch_if_modified(client, &url, has_explore, None)
+            .await
+            .with_context(|| format!("failed to fetch {url}"))?
+        {
+            self.verify_checksum(client, repo, "explore_v2.json", &fetched.body)
+                .await?;
+            let explore: serde_json::Value = serde_json::from_slice(&fetched.body)
+                .with_context(|| format!("failed to parse {url}"))?;
+            self.explore.store(Arc::new(explore));
+            self.commit_validators(&url, fetched.validators);
+            self.persist_state_file(STATE_EXPLORE_FILE, &fetched.body)
+                .await;
+        }
+        Ok(())
+    }
+
+    // 开启 verify_checksums 时，用仓库中的 `<file>.sha256`（sha256sum 格式，取首个字段）校验下载内容。
+    // 校验文件直接从 GitHub 原始地址获取，不经当前镜像，否则被篡改的镜像可以连同校验文件一起伪造
+    async fn verify_checksum(
+        &self,
+        client: &reqwest::Client,
+        repo: &RepoSource,
+        file: &str,
+        body: &[u8],
+    ) -> anyhow::Result<()> {
+        if !self.verify_checksums.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+        let url = repo.raw_url(&format!("{file}.sha256"));
+        let text = client
+            .get(&url)
+            .send()
+            .await
+            .and_then(|resp| resp.error_for_status())
+            .with_context(|| format!("failed to fetch checksum {url}"))?
+            .text()
+            .await
+            .with_context(|| format!("failed to read checksum {url}"))?;
+        let expected = text
+            .split_whitespace()
+            .next()
+            .map(str::to_ascii_lowercase)
+            .filter(|hash| hash.len() == 64 && hash.bytes().all(|b| b.is_ascii_hexdigit()))
+            .ok_or_else(|| anyhow!("invalid checksum file {url}"))?;
+        let actual = format!("{:x}", Sha256::digest(body));
+        if actual != expected {
+            log::warn!(
+                "[OfficialV2] checksum mismatch for {file}: expected {expected}, got {actual}"
+            );
+            return Err(ProviderError::ChecksumMismatch(file.to_string()).into());
+        }
+        Ok(())
+    }
+
+    // 带 If-None-Match / If-Modified-Since 的条件请求；304 时返回 None。
+    // 仅在内存中已有该资源时才发送校验头，避免 304 后无数据可用
+    async fn fetch_if_modified(
+        &self,
+        client: &reqwest::Client,
+        url: &str,
+        has_data: bool,
+        progress_cb: Option<&RefreshProgressCallback>,
+    ) -> anyhow::Result<Option<FetchedResource>> {
+        let cached = if has_data {
+            self.validators
+                .lock()
+                .ok()
+                .and_then(|map| map.get(url).cloned())
+        } else {
+            None
+        };
+
+        let mut request = client.get(url);
+        if let Some(validators) = &cached {
+            if let Some(etag) = &validators.etag {
+                request = request.header(reqwest::header::IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &validators.last_modified {
+                request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+
+        let resp = request.send().await?;
+        if resp.status() == StatusCode::NOT_MODIFIED && cached.is_some() {
+            log::info!("[OfficialV2] {} not modified", url);
+            return Ok(None);
+        }
+        let resp = resp.error_for_status()?;
+
+        let header = |name: reqwest::header::HeaderName| {
+            resp.headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(|s| s.to_string())
+        };
+        let validators = HttpValidators {
+            etag: header(reqwest::header::ETAG),
+            last_modified: header(reqwest::header::LAST_MODIFIED),
+        };
+        let body = match progress_cb {
+            Some(cb) => Self::read_body_with_progress(resp, cb).await?,
+            None => resp.bytes().await?.to_vec(),
+        };
+        Ok(Some(FetchedResource { body, validators }))
+    }
+
+    // 边读取边上报进度，200ms 节流；开始与结束时各上报一次
+    async fn read_body_with_progress(
+        resp: reqwest::Response,
+        cb: &RefreshProgressCallback,
+    ) -> anyhow::Result<Vec<u8>> {
+        let total = resp.content_length().filter(|len| *len > 0);
+        let report = |received: usize| {
+            let (progress, status) = match total {
+                Some(total) => (
+                    (received as f32 / total as f32).clamp(0.0, 1.0),
+                    "downloading",
+                ),
+                None => (0.0, "indeterminate"),
+            };
+            cb(ProgressData {
+                progress,
+                status: status.into(),
+            });
+        };
+
+        let mut body = Vec::with_capacity(total.unwrap_or(0).min(64 * 1024 * 1024) as usize);
+        let mut stream = resp.bytes_stream();
+        let mut last_emit = Instant::now();
+        report(0);
+        while let Some(chunk) = stream.next().await {
+            body.extend_from_slice(&chunk?);
+            if last_emit.elapsed() >= Duration::from_millis(200) {
+                report(body.len());
+                last_emit = Instant::now();
+            }
+        }
+        cb(ProgressData {
+            progress: 1.0,
+            status: "finished".into(),
+        });
+        Ok(body)
+    }
+
+    // 解析成功后才记录校验头，否则下次 304 会把坏数据固定下来
+    fn commit_validators(&self, url: &str, validators: HttpValidators) {
+        if let Ok(mut map) = self.validators.lock() {
+            if validators.etag.is_none() && validators.last_modified.is_none() {
+                map.remove(url);
+            } else {
+                map.insert(url.to_string(), validators);
+            }
+        }
+    }
+
+    async fn persist_validators(&self) {
+        let data = match self.validators.lock() {
+            Ok(map) => serde_json::to_vec(&*map),
+            Err(_) => return,
+        };
+        match data {
+            Ok(data) => self.persist_state_file(STATE_VALIDATORS_FILE, &data).await,
+            Err(err) => log::warn!("[OfficialV2] failed to serialize validators: {err}"),
+        }
+    }
+
+    // 解析 index_v2.csv，跳过格式错误的行并记录到报告中
+    // 表头缺少必需列时整体报错（多半是上游升级了索引格式），单行错误只跳过该行
+    fn parse_index(&self, csv_text: &str) -> anyhow::Result<(Vec<IndexV2>, RefreshReport)> {
+        let mut list: Vec<IndexV2> = Vec::new();
+        let mut skipped = Vec::new();
+        let mut csv_read = csv::ReaderBuilder::new()
+            .trim(csv::Trim::All)
+            .flexible(true) // 行尾多出的列不视为错误
+            .from_reader(csv_text.as_bytes());
+
+        let headers = csv_read
+            .headers()
+            .context("failed to read index_v2.csv header")?;
+        let missing = INDEX_V2_REQUIRED_COLUMNS
+            .iter()
+            .filter(|col| !headers.iter().any(|h| h == **col))
+            .copied()
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            return Err(anyhow!(
+                "index_v2.csv schema not supported: missing columns {}",
+                missing.join(", ")
+            ));
+        }
+
+        for (n, it) in csv_read.deserialize::<IndexV2>().enumerate() {
+            match it {
+                Ok(mut i) => {
+                    if &i.id == "<placeholder>" {
+                        let n = self.placeholder_index.load_full().clone();
+                        self.placeholder_index.store(Arc::new(*n + 1));
+                        i.id = format!("placeholder_{}", n);
+                        list.push(i);
+                    } else {
+                        list.push(i);
+                    }
+                }
+                Err(err) => {
+                    log::warn!("[OfficialV2] skipped malformed index_v2 row: {err}");
+                    // 表头占第 1 行；带位置信息时以解析器报告的行号为准
+                    let row = err.position().map(|pos| pos.line()).unwrap_or(n as u64 + 2);
+                    skipped.push(SkippedIndexRow {
+                        row,
+                        reason: err.to_string(),
+                    });
+                }
+            }
+        }
+        let report = RefreshReport {
+            parsed_rows: list.len(),
+            skipped_rows: skipped,
+        };
+        Ok((list, report))
+    }
+
+    // 最近一次解析索引时的统计，包含被跳过的行号与原因
+    pub fn last_refresh_report(&self) -> Arc<RefreshReport> {
+        self.refresh_report.load_full()
+    }
+
+    // 全部标签及资源数，按数量降序
+    pub fn get_tags(&self) -> anyhow::Result<Vec<TagInfo>> {
+        Ok((*self.tags.load_full()).clone())
+    }
+
+    // 重新生成随机排序种子；后台刷新不会打乱正在浏览的分页
+    pub fn reshuffle(&self) {
+        self.session_seed.store(Arc::new(rand::random()));
+    }
+
+    pub fn snapshot(&self) -> ProviderSnapshot {
+        let index = self.index.load();
+        let mut items_by_restype = HashMap::new();
+        let mut items_by_vendor = HashMap::new();
+        for item in index.list.iter() {
+            *items_by_restype.entry(item.restype.clone()).or_insert(0) += 1;
+            for vendor in item.device_vendors.iter().filter(|v| !v.trim().is_empty()) {
+                *items_by_vendor.entry(vendor.clone()).or_insert(0) += 1;
+            }
+        }
+
+        ProviderSnapshot {
+            total_items: index.list.len() as u64,
+            items_by_restype,
+            items_by_vendor,
+            device_count: self.device_map_all().len() as u64,
+            state: self.state.borrow().clone(),
+            last_refresh_at: self.refresh_info.load().last_refresh_at,
+            cdn: *self.cdn.load_full(),
+            offline: self.is_offline(),
+        }
+    }
+
+    pub fn debug_dump(&self) -> DebugDump {
+        DebugDump {
+            index_csv_rows: self.index.load().list.clone(),
+            device_map: (*self.device_map()).clone(),
+            explore: (*self.explore.load_full()).clone(),
+            cdn: *self.cdn.load_full(),
+            state: self.state.borrow().clone(),
+            skipped_rows: self.refresh_report.load().skipped_rows.clone(),
+        }
+    }
+
+    // 把 debug_dump 以格式化 JSON 写入文件，便于用户附在反馈中
+    pub async fn write_debug_dump(&self, path: &std::path::Path) -> anyhow::Result<()> {
+        let data = serde_json::to_vec_pretty(&self.debug_dump())
+            .with_context(|| "failed to serialize debug dump")?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .with_context(|| format!("failed to create directory {}", parent.display()))?;
+        }
+        fs::write(path, data)
+            .await
+            .with_context(|| format!("failed to write debug dump to {}", path.display()))
+    }
+
+    pub fn refresh_info(&self) -> RefreshInfo {
+        (*self.refresh_info.load_full()).clone()
+    }
+
+    // 单资源来源（如 SingleRepoProvider）直接以一个索引项作为全部索引
+    pub(crate) fn load_single_item(&self, item: IndexV2) {
+        self.load_index(vec![item]);
+    }
+
+    // 由其他来源（如 OfficialV1Provider 转换的 v1 索引）直接提供整个索引
+    pub(crate) fn load_index(&self, list: Vec<IndexV2>) {
+        self.store_index(list);
+        self.set_state(ProviderState::Ready);
+    }
+
+    pub fn has_item(&self, item_id: &str) -> bool {
+        self.index.load().by_id.contains_key(item_id)
+    }
+
+    fn store_index(&self, list: Vec<IndexV2>) {
+        // 重复 id 只保留首次出现的条目
+        let mut list = list;
+        let mut by_id: HashMap<String, usize> = HashMap::with_capacity(list.len());
+        list.retain(|item| {
+            if by_id.contains_key(&item.id) {
+                log::warn!(
+                    "[OfficialV2] duplicate index id `{}`, keeping first",
+                    item.id
+                );
+                return false;
+            }
+            by_id.insert(item.id.clone(), by_id.len());
+            true
+        });
+
+        let tags = collect_tags(&list);
+        self.suggestions
+            .store(Arc::new(build_suggestions(&list, &tags)));
+        self.tags.store(Arc::new(tags));
+        self.prune_manifest_cache(&list);
+        self.index.store(Arc::new(IndexSnapshot {
+            name_keys: list
+                .iter()
+                .map(|item| (item.id.clone(), name_collation_key(&item.name)))
+                .collect(),
+            search: SearchIndex::build(&list),
+            by_id,
+            list,
+        }));
+    }
+
+    // commit 寻址的缓存不会过期，只需剔除新索引中已不存在的版本
+    fn prune_manifest_cache(&self, list: &[IndexV2]) {
+        if let Ok(mut map) = self.manifest_cache.lock() {
+            let live = list
+                .iter()
+                .map(|item| {
+                    (
+                        item.repo_owner.as_str(),
+                        item.repo_name.as_str(),
+                        item.repo_commit_hash.as_str(),
+                    )
+                })
+                .collect::<HashSet<_>>();
+            map.retain(|(owner, repo, commit), _| {
+                live.contains(&(owner.as_str(), repo.as_str(), commit.as_str()))
+            });
+        }
+    }
+
+    // 原样保存远端数据，加载时复用同一套解析逻辑；写入失败只记录日志
+    async fn persist_state_file(&self, name: &str, data: &[u8]) {
+        if let Err(err) = self.store.write(&format!("state/{name}"), data).await {
+            log::warn!("[OfficialV2] failed to persist state {name}: {err:#}");
+        }
+    }
+
+    async fn read_state_file(&self, name: &str) -> Option<Vec<u8>> {
+        match self.store.read(&format!("state/{name}")).await {
+            Ok(data) => data,
+            Err(err) => {
+                log::warn!("[OfficialV2] failed to read cached state {name}: {err:#}");
+                None
+            }
+        }
+    }
+
+    // 从磁盘缓存恢复索引/设备表/探索页，成功载入索引时返回 true 并置为 ReadyStale。
+    // 损坏或缺失的文件会被忽略
+    pub async fn load_cached_state(&self) -> anyhow::Result<bool> {
+        let index_csv = match self.read_state_file(STATE_INDEX_FILE).await {
+            Some(raw) => sanitize_index_csv(&raw),
+            None => return Ok(false),
+        };
+        let (list, report) = match self.parse_index(&index_csv) {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                log::warn!("[OfficialV2] ignored unsupported cached index: {err:#}");
+                return Ok(false);
+            }
+        };
+        if list.is_empty() {
+            log::warn!("[OfficialV2] ignored empty or corrupted cached index");
+            return Ok(false);
+        }
+        self.refresh_report.store(Arc::new(report));
+        self.store_index(list);
+
+        match self.read_state_file(STATE_DEVICES_FILE).await {
+            Some(raw) => match serde_json::from_slice::<DeviceMapV2>(&raw) {
+                Ok(map) => self.device_map.store(Arc::new(map)),
+                Err(err) => log::warn!("[OfficialV2] ignored corrupted cached device map: {err}"),
+            },
+            None => log::warn!("[OfficialV2] cached device map unavailable"),
+        }
+
+        match self.read_state_file(STATE_EXPLORE_FILE).await {
+            Some(raw) => match serde_json::from_slice::<serde_json::Value>(&raw) {
+                Ok(explore) => self.explore.store(Arc::new(explore)),
+                Err(err) => log::warn!("[OfficialV2] ignored corrupted cached explore: {err}"),
+            },
+            None => log::warn!("[OfficialV2] cached explore unavailable"),
+        }
+
+        if let Some(raw) = self.read_state_file(STATE_VALIDATORS_FILE).await {
+            match serde_json::from_slice::<HashMap<String, HttpValidators>>(&raw) {
+                Ok(validators) => {
+                    if let Ok(mut map) = self.validators.lock() {
+                        *map = validators;
+                    }
+                }
+                Err(err) => log::warn!("[OfficialV2] ignored corrupted cached validators: {err}"),
+            }
+        }
+
+        if let Some(raw) = self.read_state_file(STATE_FIRST_SEEN_FILE).await {
+            match serde_json::from_slice::<HashMap<String, u64>>(&raw) {
+                Ok(seen) => self.first_seen.store(Arc::new(seen)),
+                Err(err) => log::warn!("[OfficialV2] ignored corrupted first seen map: {err}"),
+            }
+        }
+
+        // 沿用缓存写入时的刷新时间，条目数以实际载入为准
+        let mut info = self
+            .read_state_file(STATE_REFRESH_INFO_FILE)
+            .await
+            .and_then(|raw| serde_json::from_slice::<RefreshInfo>(&raw).ok())
+            .unwrap_or_default();
+        info.item_count = self.index.load().list.len();
+        self.refresh_info.store(Arc::new(info));
+
+        self.set_state(ProviderState::ReadyStale);
+        Ok(true)
+    }
+
+    async fn current_account_source(&self) -> AccountSourceId {
+        let Ok(app_handle) = self.app_handle() else {
+            return AccountSourceId::default();
+        };
+        account::local_storage_get_json::<AccountSourceConfig>(
+            app_handle,
+            ACCOUNT_SOURCE_STORAGE_KEY,
+        )
+        .await
+        .ok()
+        .flatten()
+        .and_then(|cfg| cfg.source)
+        .unwrap_or_default()
+    }
+
+    async fn current_astrobox_token(&self) -> anyhow::Result<String> {
+        let account = AccountStore::new(ASTROBOX_ACCOUNT_PROVIDER)
+            .load(self.app_handle()?)
+            .await
+            .context("failed to read AstroBox account")?
+            .ok_or_else(|| anyhow!("请先登录 AstroBox 账号"))?;
+        account
+            .token
+            .filter(|token| !token.trim().is_empty())
+            .ok_or_else(|| anyhow!("请先登录 AstroBox 账号"))
+    }
+
+    async fn resolve_source_cdn_download_url(
+        &self,
+        item_id: &str,
+        device: Option<&str>,
     ) -> anyhow::Result<String> {
         let token = self.current_astrobox_token().await?;
         let base_url = self.current_account_source().await.astrobox_api_base_url();
@@ -352,7 +1657,8 @@ impl OfficialV2Provider {
                 .map(str::to_string),
             node: "edgeone",
         };
-        let response = crate::net::default_client()
+        let response = self
+            .http_client()
             .post(format!("{base_url}/source-cdn/download"))
             .header("X-ASTROBOX-TOKEN", token)
             .json(&request)
@@ -396,18 +1702,9 @@ impl OfficialV2Provider {
 
     // 仅相对(同仓)路径可镜像/内联；绝对/外链/data 等返回 None 由调用方按原样处理
     fn relative_image_path(path: &str) -> Option<String> {
-        let p = path.trim();
-        if p.is_empty()
-            || p.starts_with("http://")
-            || p.starts_with("https://")
-            || p.starts_with("data:")
-            || p.starts_with("blob:")
-            || p.starts_with("tauri:")
-            || p.starts_with('/')
-        {
-            return None;
-        }
-        Some(p.trim_start_matches('/').to_string())
+        repo_relative_path(path.trim())
+            .filter(|rel| !rel.is_empty())
+            .map(str::to_string)
     }
 
     fn image_cache_get(&self, key: &str) -> Option<Arc<str>> {
@@ -425,12 +1722,8 @@ impl OfficialV2Provider {
     }
 
     // 抓取图片并编码为 data URI。优先用响应 content-type，否则按扩展名推断。
-    async fn fetch_image_data_uri(url: &str) -> anyhow::Result<String> {
-        let resp = crate::net::default_client()
-            .get(url)
-            .send()
-            .await?
-            .error_for_status()?;
+    async fn fetch_image_data_uri(client: &reqwest::Client, url: &str) -> anyhow::Result<String> {
+        let resp = client.get(url).send().await?.error_for_status()?;
         let content_type = resp
             .headers()
             .get(reqwest::header::CONTENT_TYPE)
@@ -461,7 +1754,8 @@ impl OfficialV2Provider {
                 .collect(),
             node: "edgeone",
         };
-        let response = crate::net::default_client()
+        let response = self
+            .http_client()
             .post(format!("{base_url}/source-cdn/images"))
             .header("X-ASTROBOX-TOKEN", token)
             .json(&request)
@@ -492,7 +1786,7 @@ impl OfficialV2Provider {
     // 任一步失败/非 Pro/未镜像，相应图片不入表，调用方回退原始 URL（webview 直连）。
     async fn inline_images(&self, refs: Vec<ImageRef>) -> HashMap<String, String> {
         let mut out: HashMap<String, String> = HashMap::new();
-        if refs.is_empty() {
+        if refs.is_empty() || self.is_offline() {
             return out;
         }
 
@@ -529,6 +1823,7 @@ impl OfficialV2Provider {
         };
 
         // 只内联加速直链；非加速(GitHub 兜底)留给调用方用原始 URL
+        let client = self.http_client();
         let mut tasks = Vec::new();
         for (id, entries) in signed {
             let Some((owner, repo, commit)) = coords.get(&id).cloned() else {
@@ -541,8 +1836,9 @@ impl OfficialV2Provider {
                 let key =
                     Self::image_cos_key(&owner, &repo, &commit, entry.path.trim_start_matches('/'));
                 let url = entry.url;
+                let client = client.clone();
                 tasks.push(async move {
-                    match Self::fetch_image_data_uri(&url).await {
+                    match Self::fetch_image_data_uri(&client, &url).await {
                         Ok(data) => Some((key, data)),
                         Err(err) => {
                             log::warn!("[OfficialV2] inline image failed {key}: {err}");
@@ -565,13 +1861,12 @@ impl OfficialV2Provider {
     }
 
     pub async fn get_blog_markdown(&self, path: &str) -> anyhow::Result<String> {
+        self.ensure_online()?;
         let cdn = *self.cdn.load_full();
-        let raw_url = format!(
-            "https://raw.githubusercontent.com/AstralSightStudios/AstroBox-Repo/refs/heads/main/blogs/{}",
-            path
-        );
+        let repo = self.repo.load_full();
+        let raw_url = repo.raw_url(&format!("blogs/{}", path));
         let url = cdn.convert_url(&raw_url);
-        let client = crate::net::default_client();
+        let client = self.http_client();
         let resp = client
             .get(&url)
             .send()
@@ -581,9 +1876,11 @@ impl OfficialV2Provider {
         let text = resp.text().await?;
 
         // Replace naked raw.githubusercontent.com URLs
-        let raw_re = Regex::new(
-            r#"https://raw\.githubusercontent\.com/AstralSightStudios/AstroBox-Repo/[^)\s\"']+"#,
-        )
+        let raw_re = Regex::new(&format!(
+            r#"https://raw\.githubusercontent\.com/{}/{}/[^)\s\"']+"#,
+            regex::escape(&repo.owner),
+            regex::escape(&repo.name)
+        ))
         .unwrap();
         let text = raw_re.replace_all(&text, |caps: &regex::Captures<'_>| {
             let matched = caps.get(0).unwrap().as_str();
@@ -600,10 +1897,7 @@ impl OfficialV2Provider {
         }
 
         let rel_re = Regex::new(r"(!?\[[^\]]*\])\(([^)\s]+)\)").unwrap();
-        let base_raw = format!(
-            "https://raw.githubusercontent.com/AstralSightStudios/AstroBox-Repo/refs/heads/main/blogs/{}/",
-            base_dir.trim_end_matches('/')
-        );
+        let base_raw = repo.raw_url(&format!("blogs/{}/", base_dir.trim_end_matches('/')));
         let text = rel_re.replace_all(&text, |caps: &regex::Captures<'_>| {
             let prefix = caps.get(1).unwrap().as_str();
             let link = caps.get(2).unwrap().as_str();
@@ -622,60 +1916,361 @@ impl OfficialV2Provider {
         Ok(text.into_owned())
     }
 
+    // 先查内存缓存（commit 寻址，离线时仍可命中），未命中再拉取
     pub async fn get_manifest(
         &self,
         owner: &str,
         name: &str,
         commit_hash: &str,
     ) -> anyhow::Result<ManifestV2> {
-        let base = self.build_repo_cdn_url(owner, name, commit_hash);
-        let client = crate::net::default_client();
-
-        let url_v2 = format!("{}/manifest_v2.json", base);
-        let resp_v2 = client.get(&url_v2).send().await?;
+        self.get_manifest_with(owner, name, commit_hash, false)
+            .await
+    }
 
-        if resp_v2.status() == reqwest::StatusCode::NOT_FOUND {
-            // fallback v1 manifest
-            let url_v1 = format!("{}/manifest.json", base);
-            let resp_v1 = client
-                .get(&url_v1)
-                .send()
-                .await?
-                .error_for_status()
-                .with_context(|| format!("failed to request legacy manifest `{url_v1}`"))?;
+    // bypass_cache 为 true 时强制重新拉取并覆盖缓存
+    #[tracing::instrument(
+        name = "get_manifest",
+        skip(self),
+        fields(cdn = ?*self.cdn.load_full()),
+        err(Display, level = "warn")
+    )]
+    pub async fn get_manifest_with(
+        &self,
+        owner: &str,
+        name: &str,
+        commit_hash: &str,
+        bypass_cache: bool,
+    ) -> anyhow::Result<ManifestV2> {
+        let key = (owner.to_string(), name.to_string(), commit_hash.to_string());
+        if !bypass_cache
+            && let Some(manifest) = self
+                .manifest_cache
+                .lock()
+                .ok()
+                .and_then(|map| map.get(&key).cloned())
+        {
+            return Ok((*manifest).clone());
+        }
 
-            let text_v1 = resp_v1.text().await?;
-            let raw_v1: serde_json::Value = serde_json::from_str(&text_v1)
-                .with_context(|| "failed to parse legacy manifest json")?;
+        let disk_key = manifest_disk_key(owner, name, commit_hash);
+        let cached = if bypass_cache {
+            None
+        } else {
+            self.read_manifest_file(&disk_key).await
+        };
+        let manifest = match cached {
+            Some(manifest) => manifest,
+            None => {
+                let manifest = self.fetch_manifest(owner, name, commit_hash).await?;
+                match serde_json::to_vec(&manifest) {
+                    Ok(data) => {
+                        if let Err(err) = self.store.write(&disk_key, &data).await {
+                            log::warn!("[OfficialV2] failed to persist manifest: {err:#}");
+                        }
+                    }
+                    Err(err) => log::warn!("[OfficialV2] failed to serialize manifest: {err}"),
+                }
+                manifest
+            }
+        };
 
-            let manifest_v2 = super::legacyparse::manifest_v1_to_v2(raw_v1)
-                .with_context(|| "failed to convert legacy manifest v1 -> v2")?;
+        self.remember_manifest(key, &manifest);
+        Ok(manifest)
+    }
 
-            Ok(manifest_v2)
-        } else {
-            let resp_v2 = resp_v2
-                .error_for_status()
-                .with_context(|| format!("failed to request manifest v2 `{url_v2}`"))?;
-            let text_v2 = resp_v2.text().await?;
-            let manifest: ManifestV2 = serde_json::from_str(&text_v2)?;
-            Ok(manifest)
+    fn remember_manifest(&self, key: ManifestKey, manifest: &ManifestV2) {
+        if let Ok(mut map) = self.manifest_cache.lock() {
+            if map.len() >= MANIFEST_CACHE_CAP {
+                map.clear();
+            }
+            map.insert(key, Arc::new(manifest.clone()));
         }
     }
 
-    pub async fn resolve_download_entry(
+    // 覆盖已缓存的 manifest（内存与磁盘），供补全了字段的单资源来源写回
+    pub(crate) async fn store_manifest(
         &self,
-        item_id: String,
-        device: String,
-        trial: bool,
-    ) -> anyhow::Result<ManifestDownloadV2> {
-        let index = self.index.load();
-        let index_ref = index.clone();
+        owner: &str,
+        name: &str,
+        commit_hash: &str,
+        manifest: &ManifestV2,
+    ) {
+        match serde_json::to_vec(manifest) {
+            Ok(data) => {
+                let disk_key = manifest_disk_key(owner, name, commit_hash);
+                if let Err(err) = self.store.write(&disk_key, &data).await {
+                    log::warn!("[OfficialV2] failed to persist manifest: {err:#}");
+                }
+            }
+            Err(err) => log::warn!("[OfficialV2] failed to serialize manifest: {err}"),
+        }
+        let key = (owner.to_string(), name.to_string(), commit_hash.to_string());
+        self.remember_manifest(key, manifest);
+    }
 
-        let item = index_ref
-            .iter()
-            .find(|entry| entry.id == item_id)
-            .or_else(|| index_ref.iter().find(|entry| entry.name == item_id))
-            .cloned()
+    // 损坏的缓存文件直接删除，交由调用方重新拉取
+    async fn read_manifest_file(&self, disk_key: &str) -> Option<ManifestV2> {
+        let raw = match self.store.read(disk_key).await {
+            Ok(raw) => raw?,
+            Err(err) => {
+                log::warn!("[OfficialV2] failed to read cached manifest: {err:#}");
+                return None;
+            }
+        };
+        match serde_json::from_slice::<ManifestV2>(&raw) {
+            Ok(manifest) => Some(manifest),
+            Err(err) => {
+                log::warn!("[OfficialV2] removed corrupted cached manifest {disk_key}: {err}");
+                let _ = self.store.remove(disk_key).await;
+                None
+            }
+        }
+    }
+
+    // 删除当前索引未引用的磁盘 manifest，返回删除数量
+    async fn prune_manifest_files(&self) -> anyhow::Result<usize> {
+        let live = self
+            .index
+            .load()
+            .list
+            .iter()
+            .map(|item| {
+                manifest_disk_key(&item.repo_owner, &item.repo_name, &item.repo_commit_hash)
+            })
+            .collect::<HashSet<_>>();
+        let mut removed = 0;
+        for key in self.store.list(MANIFEST_CACHE_DIR).await? {
+            if !live.contains(&key) {
+                self.store.remove(&key).await?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    // 缓存中已下载的产物，按资源 id 排序；忽略未完成的 .part 文件与内部目录
+    pub async fn list_cached(&self) -> anyhow::Result<Vec<CachedItem>> {
+        let mut items = Vec::new();
+        for dir in self.store.list("").await? {
+            if matches!(dir.as_str(), "state" | MANIFEST_CACHE_DIR | BLOB_CACHE_DIR) {
+                continue;
+            }
+            // 根目录下的普通文件 list 会失败，直接跳过
+            let Ok(keys) = self.store.list(&dir).await else {
+                continue;
+            };
+            let mut files = Vec::new();
+            for key in keys {
+                let file_name = key.rsplit('/').next().unwrap_or(&key).to_string();
+                if file_name.ends_with(".part") {
+                    continue;
+                }
+                let size = match self.store.local_path(&key) {
+                    Some(path) => match fs::metadata(&path).await {
+                        Ok(meta) if meta.is_file() => meta.len(),
+                        _ => continue,
+                    },
+                    None => match self.store.read(&key).await {
+                        Ok(Some(data)) => data.len() as u64,
+                        _ => continue,
+                    },
+                };
+                files.push((file_name, size));
+            }
+            if !files.is_empty() {
+                items.push(CachedItem {
+                    item_id: dir,
+                    files,
+                });
+            }
+        }
+        Ok(items)
+    }
+
+    // 卸载后回收某资源的缓存：下载产物目录、manifest 与内联图片。未缓存时直接返回
+    pub async fn clear_item_cache(&self, item_id: &str) -> anyhow::Result<()> {
+        let item_id = item_id.trim();
+        if item_id.is_empty()
+            || item_id.contains(['/', '\\'])
+            || item_id == "."
+            || item_id == ".."
+            || item_id == "state"
+            || item_id == MANIFEST_CACHE_DIR
+            || item_id == BLOB_CACHE_DIR
+        {
+            return Err(anyhow!("invalid item id `{item_id}`"));
+        }
+
+        // 只删除该资源自己的目录，其他资源进行中的 .part 文件不受影响
+        self.store
+            .remove(item_id)
+            .await
+            .with_context(|| format!("failed to clear cache of {item_id}"))?;
+
+        if let Some(item) = self.index_item(item_id) {
+            let (owner, repo, commit) = (
+                item.repo_owner.as_str(),
+                item.repo_name.as_str(),
+                item.repo_commit_hash.as_str(),
+            );
+            if let Ok(mut map) = self.manifest_cache.lock() {
+                map.retain(|(o, r, c), _| !(o == owner && r == repo && c == commit));
+            }
+            self.store
+                .remove(&manifest_disk_key(owner, repo, commit))
+                .await?;
+            let image_prefix = Self::image_cos_key(owner, repo, commit, "");
+            if let Ok(mut map) = self.image_b64_cache.lock() {
+                map.retain(|key, _| !key.starts_with(&image_prefix));
+            }
+        }
+        Ok(())
+    }
+
+    // 清空内存中的 manifest / 图片缓存，并清理磁盘上不再被索引引用的 manifest
+    pub async fn clear_caches(&self) -> anyhow::Result<()> {
+        if let Ok(mut map) = self.manifest_cache.lock() {
+            map.clear();
+        }
+        if let Ok(mut map) = self.image_b64_cache.lock() {
+            map.clear();
+        }
+        let removed = self.prune_manifest_files().await?;
+        log::info!("[OfficialV2] pruned {removed} cached manifests");
+        Ok(())
+    }
+
+    // 依次尝试当前 CDN 与镜像回退链，网络错误在同一镜像上有限重试。
+    // 所有镜像都返回 404 时报 ProviderError::NotFound，含网络错误时报 ProviderError::Network；
+    // 内容无法解析属于仓库问题，直接返回不再重试
+    async fn fetch_manifest(
+        &self,
+        owner: &str,
+        name: &str,
+        commit_hash: &str,
+    ) -> anyhow::Result<ManifestV2> {
+        self.ensure_online()?;
+        let raw_base = self.build_repo_raw_url(owner, name, commit_hash);
+        let mut bases = vec![(*self.cdn.load_full()).convert_url(&raw_base)];
+        for cdn in self.mirror_chain.load().iter() {
+            let base = cdn.convert_url(&raw_base);
+            if !bases.contains(&base) {
+                bases.push(base);
+            }
+        }
+        let client = self.http_client();
+        let limits = **self.manifest_limits.load();
+
+        let mut failures = Vec::new();
+        let mut all_not_found = true;
+        for base in &bases {
+            for attempt in 1..=MANIFEST_FETCH_ATTEMPTS {
+                let err = match Self::fetch_manifest_from(&client, base, limits).await {
+                    Ok(manifest) => return Ok(manifest),
+                    Err(err) => err,
+                };
+                match classify_fetch_error(&err) {
+                    FetchFailure::Fatal => return Err(err),
+                    FetchFailure::NotFound => {
+                        failures.push(format!("{err:#}"));
+                        break;
+                    }
+                    FetchFailure::Network => {
+                        all_not_found = false;
+                        tracing::warn!(base = %base, attempt, error = %format!("{err:#}"), "manifest fetch failed");
+                        if attempt == MANIFEST_FETCH_ATTEMPTS {
+                            failures.push(format!("{err:#}"));
+                        } else {
+                            tokio::time::sleep(MANIFEST_RETRY_DELAY * attempt).await;
+                        }
+                    }
+                }
+            }
+        }
+
+        let detail = failures.join("; ");
+        let repo = format!("{owner}/{name}@{commit_hash}");
+        if all_not_found {
+            Err(anyhow::Error::new(ProviderError::NotFound(repo)).context(detail))
+        } else {
+            Err(anyhow::Error::new(ProviderError::Network(repo)).context(detail))
+        }
+    }
+
+    // 从某个 CDN 基址拉取 manifest，v2 缺失时回退 v1
+    async fn fetch_manifest_from(
+        client: &reqwest::Client,
+        base: &str,
+        limits: ManifestLimits,
+    ) -> anyhow::Result<ManifestV2> {
+        let url_v2 = format!("{}/manifest_v2.json", base);
+        let text_v2 = match fetch_manifest_text(client, &url_v2, limits).await {
+            Ok(text) => text,
+            // 旧仓库只提供 v1 manifest
+            Err(err) if matches!(classify_fetch_error(&err), FetchFailure::NotFound) => {
+                return Self::fetch_legacy_manifest(client, base, limits).await;
+            }
+            Err(err) => return Err(err.context(format!("fetching manifest from {url_v2}"))),
+        };
+        // 镜像出错时常返回 HTML 页面，附上响应开头便于排查
+        let manifest = match serde_json::from_str::<ManifestV2>(&text_v2) {
+            Ok(manifest) => manifest,
+            Err(err) => {
+                let err = anyhow::Error::new(err).context(format!(
+                    "failed to parse manifest json from {url_v2}, body starts with: {}",
+                    body_preview(&text_v2)
+                ));
+                // v2 文件损坏时尝试 v1；v1 也不可用则报告 v2 的解析错误
+                return match Self::fetch_legacy_manifest(client, base, limits).await {
+                    Ok(manifest) => {
+                        log::warn!("[OfficialV2] {err:#}; using legacy manifest instead");
+                        Ok(manifest)
+                    }
+                    Err(legacy_err) => {
+                        log::debug!("[OfficialV2] legacy manifest fallback failed: {legacy_err:#}");
+                        Err(err)
+                    }
+                };
+            }
+        };
+        if manifest.is_newer_schema() {
+            log::warn!(
+                "[OfficialV2] manifest {url_v2} declares schema version {:?}, newer than supported {SUPPORTED_MANIFEST_SCHEMA}",
+                manifest.schema_version
+            );
+        }
+        Ok(manifest)
+    }
+
+    // 拉取 v1 manifest.json 并转换为 v2（含设备 key 映射），转换结果带 legacy 标记
+    async fn fetch_legacy_manifest(
+        client: &reqwest::Client,
+        base: &str,
+        limits: ManifestLimits,
+    ) -> anyhow::Result<ManifestV2> {
+        let url_v1 = format!("{}/manifest.json", base);
+        let text_v1 = fetch_manifest_text(client, &url_v1, limits)
+            .await
+            .with_context(|| format!("fetching legacy manifest from {url_v1}"))?;
+        let raw_v1: serde_json::Value = serde_json::from_str(&text_v1).with_context(|| {
+            format!(
+                "failed to parse legacy manifest json from {url_v1}, body starts with: {}",
+                body_preview(&text_v1)
+            )
+        })?;
+
+        super::legacyparse::manifest_v1_to_v2(raw_v1)
+            .with_context(|| "failed to convert legacy manifest v1 -> v2")
+    }
+
+    pub async fn resolve_download_entry(
+        &self,
+        item_id: String,
+        device: String,
+        trial: bool,
+    ) -> anyhow::Result<ManifestDownloadV2> {
+        let item = self
+            .find_item(&item_id)
             .ok_or_else(|| anyhow!("Item not found by id or name"))?;
 
         let manifest = self
@@ -684,14 +2279,7 @@ impl OfficialV2Provider {
             .with_context(|| format!("failed to fetch manifest for {}", item.name))?;
 
         let entries = if trial {
-            manifest
-                .ext
-                .get("trialDownloads")
-                .cloned()
-                .map(serde_json::from_value::<HashMap<String, ManifestDownloadV2>>)
-                .transpose()
-                .with_context(|| "failed to parse trialDownloads")?
-                .unwrap_or_default()
+            manifest.parsed_ext()?.trial_downloads.unwrap_or_default()
         } else {
             manifest.downloads.clone()
         };
@@ -707,196 +2295,684 @@ impl OfficialV2Provider {
             entry.display_name = self.device_map_id_to_name(&device);
         }
 
-        let base = self.build_repo_cdn_url_by_index_item(&item);
-        let resolved_url = if let Some(url) = &entry.url {
-            self.resolve_repo_asset_url(&base, url)
-        } else {
-            format!(
-                "{}/{}",
-                base.trim_end_matches('/'),
-                entry.file_name.trim_start_matches('/')
-            )
-        };
+        let resolved_url =
+            self.resolve_repo_asset(&item, entry.url.as_deref().unwrap_or(&entry.file_name));
         entry.url = Some(resolved_url);
 
         Ok(entry)
     }
-}
-
-#[async_trait]
-impl CommunityProvider for OfficialV2Provider {
-    fn provider_name(&self) -> String {
-        "OfficialV2".to_string()
-    }
-    fn state(&self) -> ProviderState {
-        let state = self.state.load().clone();
-        (*state).clone()
-    }
-
-    async fn refresh(&self, cfg: &str) -> anyhow::Result<()> {
-        self.state.store(Arc::new(ProviderState::Updating));
 
-        //更新cdn
+    // 解析下载目标：索引项、命中的设备键、下载条目与文件名（不涉及 URL 签发）
+    async fn resolve_artifact(
+        &self,
+        item_id: &str,
+        device: &str,
+    ) -> anyhow::Result<ResolvedArtifact> {
+        let item = self
+            .find_item(item_id)
+            .ok_or_else(|| anyhow!("Item not found by id or name"))?;
 
-        let cfg: HashMap<String, _> = serde_json::from_str(cfg).unwrap_or(HashMap::new());
-        let cdn: GitHubCdn = *cfg.get("cdn").unwrap_or(&GitHubCdn::Raw);
-        self.cdn.store(Arc::new(cdn));
-        let client = crate::net::default_client();
+        let manifest = self
+            .get_manifest(&item.repo_owner, &item.repo_name, &item.repo_commit_hash)
+            .await
+            .with_context(|| format!("failed to fetch manifest for {}", item.name))?;
 
-        // 更新index
-        let url = (*self.cdn.load_full()).convert_url("https://raw.githubusercontent.com/AstralSightStudios/AstroBox-Repo/refs/heads/main/index_v2.csv");
-        let resp = client.get(&url).send().await?.error_for_status()?;
-        let raw = resp.bytes().await?;
+        let downloads = &manifest.downloads;
+        if downloads.is_empty() {
+            return Err(ProviderError::NoArtifacts(item.id).into());
+        }
+        let (resolved_device, entry) = downloads
+            .get(device)
+            .map(|entry| (device, entry))
+            .or_else(|| downloads.get("default").map(|entry| ("default", entry)))
+            .or_else(|| {
+                downloads
+                    .iter()
+                    .next()
+                    .map(|(key, entry)| (key.as_str(), entry))
+            })
+            .map(|(key, entry)| (key.to_string(), entry.clone()))
+            .ok_or_else(|| anyhow!("no downloadable artifact for device `{device}`"))?;
 
-        let sanitized = strip_zero_width(&String::from_utf8_lossy(&raw));
-        let mut list: Vec<IndexV2> = Vec::new();
-        let mut csv_read = csv::ReaderBuilder::new()
-            .trim(csv::Trim::All)
-            .from_reader(sanitized.as_bytes());
-        for it in csv_read.deserialize::<IndexV2>() {
-            match it {
-                Ok(mut i) => {
-                    if &i.id == "<placeholder>" {
-                        let n = self.placeholder_index.load_full().clone();
-                        self.placeholder_index.store(Arc::new(*n + 1));
-                        i.id = format!("placeholder_{}", n);
-                        list.push(i);
-                    } else {
-                        list.push(i);
-                    }
-                }
-                Err(err) => {
-                    log::warn!("[OfficialV2] skipped malformed index_v2 row: {err}");
+        let mut file_name = entry.file_name.trim().to_string();
+        if file_name.is_empty() {
+            if let Some(url) = &entry.url {
+                if let Some(name) = url.split('/').last() {
+                    file_name = name.to_string();
                 }
             }
         }
-        self.index.store(Arc::new(list));
-        self.split_index(114514, SortRuleV2::Random);
+        if file_name.is_empty() {
+            return Err(anyhow!("download entry missing file name"));
+        }
 
-        // 更新设备map
-        let url = (*self.cdn.load_full()).convert_url("https://raw.githubusercontent.com/AstralSightStudios/AstroBox-Repo/refs/heads/main/devices_v2.json");
-        let resp = client.get(&url).send().await?.error_for_status()?;
-        let map: DeviceMapV2 = resp.json().await?;
-        self.device_map.store(Arc::new(map));
+        Ok(ResolvedArtifact {
+            item,
+            device: resolved_device,
+            entry,
+            file_name,
+        })
+    }
 
-        // 更新探索页
-        let url = (*self.cdn.load_full()).convert_url("https://raw.githubusercontent.com/AstralSightStudios/AstroBox-Repo/refs/heads/main/explore_v2.json");
-        let resp = client.get(&url).send().await?.error_for_status()?;
-        let explore: serde_json::Value = resp.json().await?;
-        self.explore.store(Arc::new(explore));
+    // 在 resolve_artifact 基础上解析最终下载 URL
+    async fn resolve_download(
+        &self,
+        item_id: &str,
+        device: &str,
+    ) -> anyhow::Result<ResolvedDownload> {
+        let ResolvedArtifact {
+            item,
+            device: resolved_device,
+            entry,
+            file_name,
+        } = self.resolve_artifact(item_id, device).await?;
+
+        if let Some((_, target)) = self.device_by_id(device)
+            && let Err(reason) = self.check_compatibility(&item, &target)
+        {
+            if self.strict_compatibility.load(Ordering::SeqCst) {
+                return Err(ProviderError::Incompatible(reason).into());
+            }
+            log::warn!("[OfficialV2] {reason}");
+        }
 
-        self.state.store(Arc::new(ProviderState::Ready));
+        let cdn = *self.cdn.load_full();
+        let raw_url =
+            self.resolve_repo_asset_raw(&item, entry.url.as_deref().unwrap_or(&file_name));
+        let url = if cdn.uses_astrobox_source_cdn() {
+            self.resolve_source_cdn_download_url(&item.id, Some(&resolved_device))
+                .await?
+        } else {
+            cdn.convert_url(&raw_url)
+        };
 
-        Ok(())
+        Ok(ResolvedDownload {
+            item,
+            entry,
+            file_name,
+            url,
+            raw_url,
+        })
     }
 
-    async fn get_page(
+    // 检查单个已安装条目：返回最新版本与自已安装版本以来的更新日志
+    pub async fn check_update(
         &self,
-        page: u32,
-        limit: u32,
-        search: SearchConfig,
-    ) -> anyhow::Result<Vec<ManifestItemV2>> {
-        let index = self.index.load().clone();
-        let mut filtered_index = (*index).clone();
-
-        // 先根据搜索条件过滤整个索引
-        if let Some(categories) = &search.category {
-            let hide_paid = categories.contains(&HIDE_PAID.to_string());
-            let hide_force_paid = categories.contains(&HIDE_FORCE_PAID.to_string());
-            let quick_app = categories.contains(&QUICK_APP.to_string());
-            let watchface = categories.contains(&WATCHFACE.to_string());
-            let mut devices = Vec::new();
-
-            self.device_map()
-                .xiaomi
-                .values()
-                .filter(|e| categories.contains(&e.name))
-                .for_each(|e| {
-                    devices.push(e.id.clone());
-                });
+        item_id: &str,
+        device: &str,
+        installed_version: &str,
+    ) -> anyhow::Result<UpdateInfo> {
+        let artifact = self.resolve_artifact(item_id, device).await?;
+        let entry = &artifact.entry;
+        let update_available = crate::version::is_newer(&entry.version, installed_version);
+        let updatelogs = if update_available {
+            entry.logs_since(installed_version).to_vec()
+        } else {
+            Vec::new()
+        };
+        Ok(UpdateInfo {
+            update_available,
+            latest_version: entry.version.clone(),
+            updatelogs,
+        })
+    }
 
-            let res_type = if quick_app && watchface {
-                None
-            } else if quick_app {
-                Some(ResourceTypeV2::QuickApp)
-            } else if watchface {
-                Some(ResourceTypeV2::WatchFace)
-            } else {
-                None
-            };
+    // 对比已安装版本与仓库当前产物版本，规则见 crate::version
+    pub async fn has_update(
+        &self,
+        item_id: String,
+        device: String,
+        installed_version: String,
+    ) -> anyhow::Result<bool> {
+        Ok(self
+            .check_update(&item_id, &device, &installed_version)
+            .await?
+            .update_available)
+    }
 
-            filtered_index.retain(|item| {
-                (item
-                    .devices
-                    .iter()
-                    .any(|category| devices.contains(category))
-                    || devices.is_empty())
-                    && !(item.paid_type == PaidTypeV2::ForcePaid && hide_force_paid)
-                    && !(item.paid_type == PaidTypeV2::Paid && hide_paid)
-                    && (if let Some(t) = &res_type {
-                        &item.restype == t
-                    } else {
-                        true
-                    })
-            });
-        }
+    // 批量检查更新：并发拉取 manifest，仅返回有更新、已下架或检查失败的条目
+    pub async fn check_updates(&self, installed: Vec<InstalledRef>) -> Vec<UpdateAvailable> {
+        futures_util::stream::iter(installed)
+            .map(|installed| async move {
+                let status = if self.find_item(&installed.item_id).is_none() {
+                    Some(UpdateStatus::Removed)
+                } else {
+                    match self
+                        .resolve_artifact(&installed.item_id, &installed.device)
+                        .await
+                    {
+                        Ok(artifact)
+                            if crate::version::is_newer(
+                                &artifact.entry.version,
+                                &installed.version,
+                            ) =>
+                        {
+                            Some(UpdateStatus::Available(artifact.entry.version))
+                        }
+                        Ok(_) => None,
+                        Err(err) => Some(UpdateStatus::Failed(format!("{err:#}"))),
+                    }
+                };
+                status.map(|status| UpdateAvailable {
+                    item_id: installed.item_id,
+                    device: installed.device,
+                    installed_version: installed.version,
+                    status,
+                })
+            })
+            .buffer_unordered(MANIFEST_FETCH_CONCURRENCY)
+            .filter_map(|update| async move { update })
+            .collect()
+            .await
+    }
 
-        if let Some(keyword) = &search.filter {
-            let keyword_lower = keyword.to_lowercase();
-            filtered_index.retain(|item| {
-                item.name.to_lowercase().contains(&keyword_lower)
-                    || item.tags.iter().any(|tag| tag.to_lowercase().contains(&keyword_lower))
-            });
+    // 并发预取一批资源的 manifest 写入缓存，已缓存的条目直接跳过。
+    // 每个 manifest 完整取回后才写入缓存，调用方中途丢弃 future 不会留下半截数据
+    pub async fn prefetch_manifests(
+        &self,
+        item_ids: &[String],
+        concurrency: usize,
+    ) -> anyhow::Result<PrefetchReport> {
+        let mut report = PrefetchReport::default();
+        let mut seen = HashSet::new();
+        let mut pending = Vec::new();
+        {
+            let cache = self
+                .manifest_cache
+                .lock()
+                .map_err(|_| anyhow!("manifest cache poisoned"))?;
+            for item_id in item_ids {
+                if !seen.insert(item_id.as_str()) {
+                    continue;
+                }
+                let Some(item) = self.find_item(item_id) else {
+                    report
+                        .failed
+                        .push((item_id.clone(), "Item not found".to_string()));
+                    continue;
+                };
+                let key = (
+                    item.repo_owner.clone(),
+                    item.repo_name.clone(),
+                    item.repo_commit_hash.clone(),
+                );
+                if cache.contains_key(&key) {
+                    report.cached.push(item_id.clone());
+                } else {
+                    pending.push((item_id.clone(), item));
+                }
+            }
         }
 
-        // 对过滤后的结果进行排序
-        // 注意：ThreadRng 非 Send，必须在后续 .await 之前丢弃，故就地取用
-        match &search.sort {
-            SortRuleV2::Random => filtered_index.shuffle(&mut rand::rng()),
-            SortRuleV2::Name => {
-                filtered_index.sort_by(|a, b| a.name.cmp(&b.name));
-            }
-            SortRuleV2::Time => {
-                filtered_index.reverse();
+        let results = futures_util::stream::iter(pending)
+            .map(|(item_id, item)| async move {
+                let result = self
+                    .get_manifest(&item.repo_owner, &item.repo_name, &item.repo_commit_hash)
+                    .await;
+                (item_id, result)
+            })
+            .buffer_unordered(concurrency.max(1))
+            .collect::<Vec<_>>()
+            .await;
+        for (item_id, result) in results {
+            match result {
+                Ok(_) => report.fetched.push(item_id),
+                Err(err) => report.failed.push((item_id, format!("{err:#}"))),
             }
-        };
-
-        // 对过滤并排序后的结果分页
-        let start = (page as usize) * (limit as usize);
-        if start >= filtered_index.len() {
-            return Ok(Vec::new());
         }
+        Ok(report)
+    }
 
-        let end = std::cmp::min(start + limit as usize, filtered_index.len());
-        let target_page = &filtered_index[start..end];
+    // manifest 实际提供产物的设备；`default` 产物视为适用于所有需要拉取资源的设备
+    pub async fn get_supported_devices(&self, item_id: &str) -> anyhow::Result<Vec<DeviceV2>> {
+        let item = self
+            .find_item(item_id)
+            .ok_or_else(|| anyhow!("Item not found by id or name"))?;
+        let manifest = self
+            .get_manifest(&item.repo_owner, &item.repo_name, &item.repo_commit_hash)
+            .await
+            .with_context(|| format!("failed to fetch manifest for {}", item.name))?;
 
-        let mut ret = Vec::new();
-        for item in target_page.iter() {
-            ret.push(ManifestItemV2 {
-                id: item.id.clone(),
-                name: item.name.clone(),
-                preview: vec![format!(
-                    "{}/{}",
-                    self.build_repo_cdn_url_by_index_item(&item),
-                    item.cover.clone()
-                )],
-                icon: format!(
-                    "{}/{}",
-                    self.build_repo_cdn_url_by_index_item(&item),
-                    item.icon.clone()
-                ),
-                cover: format!(
-                    "{}/{}",
-                    self.build_repo_cdn_url_by_index_item(&item),
-                    item.cover.clone()
-                ),
-                paid_type: Some(item.paid_type.clone()),
+        let device_map = self.device_map.load();
+        let all_default = manifest.downloads.contains_key("default");
+        let mut devices = device_map
+            .xiaomi
+            .values()
+            .chain(device_map.vivo.values())
+            .filter(|dev| manifest.downloads.contains_key(&dev.id) || (all_default && dev.fetch))
+            .cloned()
+            .collect::<Vec<_>>();
+        devices.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+        devices.dedup_by(|a, b| a.id == b.id);
+        Ok(devices)
+    }
+
+    // 以 manifest 产物判断资源能否安装到某设备；设备 id 不在设备表中时报错
+    pub async fn is_device_supported(
+        &self,
+        item_id: &str,
+        device_id: &str,
+    ) -> anyhow::Result<bool> {
+        if self.device_by_id(device_id).is_none() {
+            return Err(anyhow!("unknown device id `{device_id}`"));
+        }
+        Ok(self
+            .get_supported_devices(item_id)
+            .await?
+            .iter()
+            .any(|dev| dev.id == device_id))
+    }
+
+    // 资源是否有可下载产物，供 UI 禁用下载按钮；未知资源或 manifest 不可用时视为不可下载
+    pub async fn is_downloadable(&self, item_id: &str) -> bool {
+        let Some(item) = self.find_item(item_id) else {
+            return false;
+        };
+        match self
+            .get_manifest(&item.repo_owner, &item.repo_name, &item.repo_commit_hash)
+            .await
+        {
+            Ok(manifest) => !manifest.downloads.is_empty(),
+            Err(err) => {
+                log::warn!("[OfficialV2] manifest unavailable for {}: {err:#}", item.id);
+                false
+            }
+        }
+    }
+
+    // 重新计算已缓存产物的 sha256 并与 manifest 比对，供“检查文件”之类的功能触发重新下载
+    pub async fn verify_cached(
+        &self,
+        item_id: String,
+        device: String,
+    ) -> anyhow::Result<VerifyStatus> {
+        let artifact = self.resolve_artifact(&item_id, &device).await?;
+        let path = self.artifact_path(&artifact.item.id, &artifact.file_name)?;
+
+        let mut file = match File::open(&path).await {
+            Ok(file) => file,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(VerifyStatus::Missing);
+            }
+            Err(err) => {
+                return Err(err).with_context(|| format!("failed to open {}", path.display()));
+            }
+        };
+
+        let Some(expected) = artifact
+            .entry
+            .sha256
+            .as_deref()
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_ascii_lowercase)
+        else {
+            return Ok(VerifyStatus::Unknown);
+        };
+
+        if sha256_file(&mut file, &path).await? == expected {
+            Ok(VerifyStatus::Present)
+        } else {
+            Ok(VerifyStatus::Corrupt)
+        }
+    }
+
+    // 发送下载请求并按策略处理重定向，避免镜像 302 回 raw.githubusercontent.com 绕开所选 CDN
+    async fn send_download_request(&self, url: &str) -> anyhow::Result<reqwest::Response> {
+        self.ensure_online()?;
+        let policy = *self.redirect_policy.load_full();
+        let cdn = *self.cdn.load_full();
+        if policy == CdnRedirectPolicy::Follow || matches!(cdn.normalized(), GitHubCdn::Raw) {
+            return self
+                .http_client()
+                .get(url)
+                .send()
+                .await
+                .with_context(|| format!("failed to request {}", url));
+        }
+
+        // 注入的客户端按其自身的重定向配置工作，自行跟随时只能按最终地址检查策略
+        let client = match &self.client {
+            Some(client) => client.clone(),
+            None => crate::net::default_client_builder()
+                .redirect(reqwest::redirect::Policy::none())
+                .build()
+                .context("failed to build download client")?,
+        };
+        let mut current = url.to_string();
+        for _ in 0..MAX_DOWNLOAD_REDIRECTS {
+            let response = client
+                .get(&current)
+                .send()
+                .await
+                .with_context(|| format!("failed to request {}", current))?;
+            if response.url().as_str() != reqwest::Url::parse(&current)?.as_str() {
+                check_followed_redirect(policy, cdn, &current, response.url())?;
+                return Ok(response);
+            }
+            if !response.status().is_redirection() {
+                return Ok(response);
+            }
+
+            let location = response
+                .headers()
+                .get(reqwest::header::LOCATION)
+                .and_then(|v| v.to_str().ok())
+                .ok_or_else(|| anyhow!("redirect without location from {}", current))?;
+            let base = reqwest::Url::parse(&current)?;
+            let next = base
+                .join(location)
+                .with_context(|| format!("invalid redirect location `{location}`"))?;
+
+            let next_url = match policy {
+                CdnRedirectPolicy::DenyCrossHost if next.host_str() != base.host_str() => {
+                    return Err(anyhow!(
+                        "refused cross-host redirect {} -> {}",
+                        current,
+                        next
+                    ));
+                }
+                CdnRedirectPolicy::ReapplyCdn => cdn.convert_url(next.as_str()),
+                _ => next.to_string(),
+            };
+            if next_url == current {
+                // 镜像把请求打回源站，重新套用 CDN 后又回到自身
+                return Err(anyhow!("mirror redirected {} back to the origin", current));
+            }
+            current = next_url;
+        }
+
+        Err(anyhow!("too many redirects while downloading {}", url))
+    }
+
+    // 依次尝试当前地址与镜像回退链中的各镜像，返回第一个成功的响应。
+    // 只在建立连接/响应出错时切换镜像，传输中途失败不重试；全部失败时汇总每个镜像的错误
+    async fn open_download(
+        &self,
+        resolved: &ResolvedDownload,
+    ) -> anyhow::Result<reqwest::Response> {
+        let mut candidates = vec![("current".to_string(), resolved.url.clone())];
+        for cdn in self.mirror_chain.load().iter() {
+            let url = cdn.convert_url(&resolved.raw_url);
+            if !candidates.iter().any(|(_, tried)| *tried == url) {
+                candidates.push((format!("{cdn:?}"), url));
+            }
+        }
+
+        let mut failures = Vec::new();
+        for (label, url) in &candidates {
+            let result = match self.send_download_request(url).await {
+                Ok(response) => response
+                    .error_for_status()
+                    .with_context(|| format!("download request returned error for {url}")),
+                Err(err) => Err(err),
+            };
+            match result {
+                Ok(response) => {
+                    tracing::Span::current().record("url", url.as_str());
+                    return Ok(response);
+                }
+                // 离线等明确的拒绝无需再试其他镜像
+                Err(err) if err.downcast_ref::<ProviderError>().is_some() => return Err(err),
+                Err(err) => {
+                    tracing::warn!(mirror = %label, url = %url, error = %format!("{err:#}"), "download request failed");
+                    failures.push(MirrorFailure {
+                        mirror: label.clone(),
+                        url: url.clone(),
+                        error: err,
+                    });
+                }
+            }
+        }
+
+        // 只有一个候选时原样返回它的错误，便于调用方 downcast
+        match failures.len() {
+            0 => Err(anyhow!("no download url for {}", resolved.file_name)),
+            1 => Err(failures.remove(0).error),
+            _ => Err(MirrorFailures {
+                file_name: resolved.file_name.clone(),
+                failures,
+            }
+            .into()),
+        }
+    }
+
+    // 把下载流写入任意 writer，负责进度回调与 sha256 校验（manifest 提供时）
+    async fn stream_to_writer<W>(
+        &self,
+        resolved: &ResolvedDownload,
+        writer: &mut W,
+        progress_cb: &mut Option<Box<dyn Fn(ProgressData) + Send>>,
+    ) -> anyhow::Result<u64>
+    where
+        W: AsyncWrite + Unpin + Send,
+    {
+        if let Some(cb) = progress_cb.as_ref() {
+            cb(ProgressData {
+                progress: 0.0,
+                status: "".into(),
+            });
+        }
+
+        let response = self.open_download(resolved).await?;
+        let downloaded = download::stream_response(
+            response,
+            writer,
+            &resolved.file_name,
+            resolved.entry.sha256.as_deref(),
+            progress_cb,
+        )
+        .await?;
+
+        tracing::Span::current().record("bytes", downloaded);
+        Ok(downloaded)
+    }
+
+    // 下载相关的 span；url（实际成功的镜像地址）与 bytes 在过程中补记
+    fn download_span(&self, item_id: &str, device: &str) -> tracing::Span {
+        tracing::info_span!(
+            "download",
+            item_id = %item_id,
+            device = %device,
+            cdn = ?*self.cdn.load_full(),
+            url = tracing::field::Empty,
+            bytes = tracing::field::Empty,
+        )
+    }
+
+    // 直接把产物流式写入调用方提供的 writer（如刷机通道、用户选定的文件），不落缓存目录
+    pub async fn download_to<W>(
+        &self,
+        item_id: String,
+        device: String,
+        mut writer: W,
+        mut progress_cb: Option<Box<dyn Fn(ProgressData) + Send>>,
+    ) -> anyhow::Result<u64>
+    where
+        W: AsyncWrite + Unpin + Send,
+    {
+        let span = self.download_span(&item_id, &device);
+        let resolved = self
+            .resolve_download(&item_id, &device)
+            .instrument(span.clone())
+            .await?;
+        let written = self
+            .stream_to_writer(&resolved, &mut writer, &mut progress_cb)
+            .instrument(span)
+            .await?;
+
+        if let Some(cb) = progress_cb.as_ref() {
+            cb(ProgressData {
+                progress: 1.0,
+                status: "finished".into(),
+            });
+        }
+
+        Ok(written)
+    }
+
+    // 按搜索条件过滤整个索引并排序。有关键词时先经倒排索引取候选，只复制候选项，
+    // 其余条件在候选上判断
+    fn filter_index(&self, search: &SearchConfig) -> Vec<IndexV2> {
+        let index = self.index.load_full();
+
+        // 多关键词：默认 AND，每个词（或引号短语）都需命中名称或任一标签（索引暂无简介字段）；
+        // KeywordMode::Any 时命中任一即可
+        let terms = search
+            .filter
+            .as_deref()
+            .map(split_search_terms)
+            .unwrap_or_default();
+        let mut filtered_index = if terms.is_empty() {
+            index.list.clone()
+        } else {
+            let match_any = search.keyword_mode == KeywordMode::Any;
+            let mut hits: Option<HashSet<usize>> = None;
+            for term in &terms {
+                let term_hits = index.search.term_hits(term, &index.list);
+                hits = Some(match hits {
+                    None => term_hits,
+                    Some(acc) if match_any => acc.union(&term_hits).copied().collect(),
+                    Some(acc) => acc.intersection(&term_hits).copied().collect(),
+                });
+            }
+            // 保持索引顺序（时间排序依赖它）
+            let mut hits = hits.unwrap_or_default().into_iter().collect::<Vec<_>>();
+            hits.sort_unstable();
+            hits.into_iter()
+                .map(|pos| index.list[pos].clone())
+                .collect()
+        };
+
+        // 分类筛选
+        if let Some(categories) = &search.category {
+            let hide_paid = categories.contains(&HIDE_PAID.to_string());
+            let hide_force_paid = categories.contains(&HIDE_FORCE_PAID.to_string());
+            let quick_app = categories.contains(&QUICK_APP.to_string());
+            let watchface = categories.contains(&WATCHFACE.to_string());
+            let mut devices = Vec::new();
+
+            self.device_map()
+                .xiaomi
+                .values()
+                .filter(|e| categories.contains(&e.name))
+                .for_each(|e| {
+                    devices.push(e.id.clone());
+                });
+
+            let res_type = if quick_app && watchface {
+                None
+            } else if quick_app {
+                Some(ResourceTypeV2::QuickApp)
+            } else if watchface {
+                Some(ResourceTypeV2::WatchFace)
+            } else {
+                None
+            };
+
+            filtered_index.retain(|item| {
+                (item
+                    .devices
+                    .iter()
+                    .any(|category| devices.contains(category))
+                    || devices.is_empty())
+                    && !(item.paid_type == PaidTypeV2::ForcePaid && hide_force_paid)
+                    && !(item.paid_type == PaidTypeV2::Paid && hide_paid)
+                    && (if let Some(t) = &res_type {
+                        &item.restype == t
+                    } else {
+                        true
+                    })
+            });
+        }
+
+        if let Some(chip) = search.chip {
+            let chip_devices = self
+                .devices_by_chip(chip)
+                .into_iter()
+                .map(|dev| dev.id)
+                .collect::<Vec<_>>();
+            filtered_index.retain(|item| item.devices.iter().any(|d| chip_devices.contains(d)));
+        }
+
+        // 排除条件在正向筛选与关键词之后生效
+        if let Some(restypes) = search.exclude_restype.as_ref().filter(|r| !r.is_empty()) {
+            filtered_index.retain(|item| !restypes.contains(&item.restype));
+        }
+        if let Some(exclude_tags) = &search.exclude_tags {
+            let exclude_tags = exclude_tags
+                .iter()
+                .map(|tag| tag.trim().to_lowercase())
+                .filter(|tag| !tag.is_empty())
+                .collect::<Vec<_>>();
+            if !exclude_tags.is_empty() {
+                filtered_index.retain(|item| {
+                    !item
+                        .tags
+                        .iter()
+                        .any(|tag| exclude_tags.contains(&tag.trim().to_lowercase()))
+                });
+            }
+        }
+
+        // 对过滤后的结果进行排序
+        let order = search.order.unwrap_or(search.sort.default_order());
+        let seed = search.seed.unwrap_or(*self.session_seed.load_full());
+        Self::sort_index(
+            &mut filtered_index,
+            &index.name_keys,
+            search.sort,
+            order,
+            seed,
+        );
+
+        filtered_index
+    }
+
+    // 把索引项转换为列表项（图片地址经 CDN 转换，不内联）
+    fn index_to_list_items(&self, target_page: &[IndexV2]) -> Vec<ManifestItemV2> {
+        let mut ret = Vec::new();
+        for item in target_page.iter() {
+            ret.push(ManifestItemV2 {
+                id: item.id.clone(),
+                name: item.name.clone(),
+                preview: vec![self.resolve_repo_asset(item, &item.cover)],
+                icon: self.resolve_repo_asset(item, &item.icon),
+                cover: self.resolve_repo_asset(item, &item.cover),
+                paid_type: Some(item.paid_type.clone()),
                 restype: item.restype.clone(),
+                tags: non_empty_values(&item.tags),
+                devices: non_empty_values(&item.devices),
 
                 ..Default::default()
             });
         }
 
+        // 列表只有索引数据；已缓存 manifest 的条目补上多语言字段
+        let lang = self.preferred_language.load_full();
+        if let Ok(map) = self.manifest_cache.lock() {
+            for (entry, item) in ret.iter_mut().zip(target_page.iter()) {
+                let key = (
+                    item.repo_owner.clone(),
+                    item.repo_name.clone(),
+                    item.repo_commit_hash.clone(),
+                );
+                if let Some(manifest) = map.get(&key) {
+                    entry.name_i18n = manifest.item.name_i18n.clone();
+                    entry.description_i18n = manifest.item.description_i18n.clone();
+                    if let Some(lang) = lang.as_deref() {
+                        entry.localize(lang);
+                    }
+                }
+            }
+        }
+        ret
+    }
+
+    // 把一页索引项转换为列表项，官方镜像源下内联图片
+    async fn build_page_items(&self, target_page: &[IndexV2]) -> Vec<ManifestItemV2> {
+        let mut ret = self.index_to_list_items(target_page);
+
         // 官方镜像源：把本页 icon/cover 经境内 CDN 内联为 base64，避免 webview 直连 GitHub
         if self.cdn.load_full().uses_astrobox_source_cdn() {
             let mut refs = Vec::new();
@@ -935,333 +3011,1935 @@ impl CommunityProvider for OfficialV2Provider {
             }
         }
 
-        Ok(ret)
+        ret
+    }
+
+    // 一次返回全部匹配项，供导出、建立外部搜索索引等场景使用，无需循环 get_page。
+    // search 为 None 时返回整个索引（按索引顺序）；图片不做 base64 内联，避免一次抓取全部图片
+    pub fn all_items(&self, search: Option<SearchConfig>) -> Vec<ManifestItemV2> {
+        match search {
+            Some(search) => self.index_to_list_items(&self.filter_index(&search)),
+            None => self.index_to_list_items(&self.index.load().list),
+        }
+    }
+
+    // 游标分页，适合无限滚动：游标记住随机种子与上一页最后一项，
+    // 期间 reshuffle 或后台刷新都不会打乱已展示的顺序。上一页最后一项已不在结果中时退回按位置续接
+    pub async fn get_page_cursor(
+        &self,
+        cursor: Option<String>,
+        limit: u32,
+        mut search: SearchConfig,
+    ) -> anyhow::Result<CursorPage<ManifestItemV2>> {
+        if limit == 0 {
+            return Err(anyhow!("limit must be greater than 0"));
+        }
+        let cursor = cursor
+            .as_deref()
+            .map(str::trim)
+            .filter(|c| !c.is_empty())
+            .map(PageCursor::decode)
+            .transpose()?;
+        let seed = cursor
+            .as_ref()
+            .map(|c| c.seed)
+            .or(search.seed)
+            .unwrap_or(*self.session_seed.load_full());
+        search.seed = Some(seed);
+
+        let filtered_index = self.filter_index(&search);
+        let start = match &cursor {
+            None => 0,
+            Some(cursor) => filtered_index
+                .iter()
+                .position(|item| item.id == cursor.after)
+                .map(|pos| pos + 1)
+                .unwrap_or(cursor.offset)
+                .min(filtered_index.len()),
+        };
+        let end = cmp::min(start.saturating_add(limit as usize), filtered_index.len());
+        let items = self.build_page_items(&filtered_index[start..end]).await;
+        let next_cursor = (end > start && end < filtered_index.len()).then(|| {
+            PageCursor {
+                seed,
+                after: filtered_index[end - 1].id.clone(),
+                offset: end,
+            }
+            .encode()
+        });
+
+        Ok(CursorPage { items, next_cursor })
+    }
+
+    // 分页查询，附带按过滤结果计算的分页信息
+    pub async fn get_page_v2(
+        &self,
+        page: u32,
+        limit: u32,
+        search: SearchConfig,
+    ) -> anyhow::Result<Page<ManifestItemV2>> {
+        if limit == 0 {
+            return Err(anyhow!("limit must be greater than 0"));
+        }
+        let filtered_index = self.filter_index(&search);
+        // 超过结果总数的 limit 只影响切片范围，返回值原样回显调用方请求的 limit
+        let total_items = filtered_index.len() as u64;
+        let total_pages = total_items.div_ceil(limit as u64);
+
+        // 对过滤并排序后的结果分页
+        let start = (page as usize).saturating_mul(limit as usize);
+        let items = if start >= filtered_index.len() {
+            Vec::new()
+        } else {
+            let end = std::cmp::min(start + limit as usize, filtered_index.len());
+            self.build_page_items(&filtered_index[start..end]).await
+        };
+
+        Ok(Page {
+            items,
+            page,
+            limit,
+            total_items,
+            total_pages,
+            has_next: (page as u64 + 1) < total_pages,
+        })
+    }
+
+    pub async fn get_page_meta(
+        &self,
+        page: u32,
+        limit: u32,
+        search: SearchConfig,
+    ) -> anyhow::Result<PageResult> {
+        self.get_page_v2(page, limit, search).await
+    }
+
+    // 自 since 以来新上架的资源，最新在前
+    pub async fn get_recent_items(
+        &self,
+        since: SystemTime,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ManifestItemV2>> {
+        let since = unix_millis(since).max(1);
+        let seen = self.first_seen.load();
+        let snapshot = self.index.load();
+        let index = &snapshot.list;
+
+        let mut recent = index
+            .iter()
+            .enumerate()
+            .filter_map(|(pos, item)| {
+                let ts = *seen.get(&item.id)?;
+                (ts >= since).then_some((ts, pos))
+            })
+            .collect::<Vec<_>>();
+        // 同一次刷新新增的条目按索引顺序倒序（索引按添加时间升序）
+        recent.sort_by(|a, b| b.cmp(a));
+
+        let items = recent
+            .into_iter()
+            .take(limit)
+            .map(|(_, pos)| index[pos].clone())
+            .collect::<Vec<_>>();
+        Ok(self.build_page_items(&items).await)
+    }
+
+    // "最近更新"：基于实时索引而非探索页。索引暂无 updated_at 字段，按首次出现时间倒序，
+    // 无记录的条目排在其后并按索引顺序倒序（索引按添加时间升序）
+    pub async fn recent(&self, limit: usize) -> Vec<ManifestItemV2> {
+        let seen = self.first_seen.load();
+        let snapshot = self.index.load();
+        let index = &snapshot.list;
+
+        let mut order = index
+            .iter()
+            .enumerate()
+            .map(|(pos, item)| (seen.get(&item.id).copied().unwrap_or(0), pos))
+            .collect::<Vec<_>>();
+        order.sort_by(|a, b| b.cmp(a));
+
+        let items = order
+            .into_iter()
+            .take(limit)
+            .map(|(_, pos)| index[pos].clone())
+            .collect::<Vec<_>>();
+        self.build_page_items(&items).await
+    }
+
+    // "猜你喜欢"：按标签 Jaccard 相似度、同类型、同作者与共同支持设备打分，取前 limit 个
+    pub async fn get_related_items(
+        &self,
+        item_id: &str,
+        limit: usize,
+    ) -> anyhow::Result<Vec<ManifestItemV2>> {
+        let target = self
+            .index_item(item_id)
+            .ok_or_else(|| anyhow!("Item not found"))?;
+
+        let snapshot = self.index.load();
+        let index = &snapshot.list;
+        let mut scored = index
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| item.id != target.id)
+            .map(|(pos, item)| (related_score(&target, item), pos))
+            .filter(|(score, _)| *score > 0.0)
+            .collect::<Vec<_>>();
+        // 分数相同按索引顺序，保证结果稳定
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+
+        let related = scored
+            .into_iter()
+            .take(limit)
+            .map(|(_, pos)| index[pos].clone())
+            .collect::<Vec<_>>();
+        Ok(self.build_page_items(&related).await)
+    }
+
+    // 与 get_page 相同，额外给出关键词命中的字段与字节区间；不需要高亮时请用 get_page
+    pub async fn search_detailed(
+        &self,
+        page: u32,
+        limit: u32,
+        search: SearchConfig,
+    ) -> anyhow::Result<Vec<SearchHit>> {
+        if limit == 0 {
+            return Err(anyhow!("limit must be greater than 0"));
+        }
+        let filtered_index = self.filter_index(&search);
+        let start = (page as usize).saturating_mul(limit as usize);
+        if start >= filtered_index.len() {
+            return Ok(Vec::new());
+        }
+        let end = std::cmp::min(start + limit as usize, filtered_index.len());
+        let target_page = &filtered_index[start..end];
+        let items = self.build_page_items(target_page).await;
+
+        let terms = search
+            .filter
+            .as_deref()
+            .map(split_search_terms)
+            .unwrap_or_default();
+
+        Ok(items
+            .into_iter()
+            .zip(target_page.iter())
+            .map(|(item, idx)| {
+                let mut matches = Vec::new();
+                let ranges = find_terms_ranges(&idx.name, &terms);
+                if !ranges.is_empty() {
+                    matches.push(SearchMatch {
+                        field: MatchField::Name,
+                        value: idx.name.clone(),
+                        ranges,
+                    });
+                }
+                for tag in &idx.tags {
+                    let ranges = find_terms_ranges(tag, &terms);
+                    if !ranges.is_empty() {
+                        matches.push(SearchMatch {
+                            field: MatchField::Tag,
+                            value: tag.clone(),
+                            ranges,
+                        });
+                    }
+                }
+                SearchHit { item, matches }
+            })
+            .collect())
+    }
+
+    // 拉取 manifest 并与索引行比对，提前暴露仓库侧 id / 类型写错的问题
+    pub async fn get_validated_manifest(&self, item_id: String) -> anyhow::Result<ManifestV2> {
+        let item = self
+            .index_item(&item_id)
+            .ok_or_else(|| anyhow!("Item not found"))?;
+
+        let mut manifest = self
+            .get_manifest(&item.repo_owner, &item.repo_name, &item.repo_commit_hash)
+            .await
+            .with_context(|| format!("failed to fetch manifest for {}", item.name))?;
+
+        backfill_legacy_identity(&mut manifest, &item);
+        validate_manifest(&manifest, Some(&item))?;
+        Ok(manifest)
+    }
+
+    // 所有可引用字段都解析为绝对 CDN 地址的 manifest（不做 base64 内联），
+    // 前端无需了解仓库结构即可直接使用
+    pub async fn get_resolved_manifest(&self, item_id: &str) -> anyhow::Result<ManifestV2> {
+        let item = self
+            .find_item(item_id)
+            .ok_or_else(|| anyhow!("Item not found"))?;
+        let mut manifest = self
+            .get_manifest(&item.repo_owner, &item.repo_name, &item.repo_commit_hash)
+            .await?;
+        backfill_legacy_identity(&mut manifest, &item);
+        warn_invalid_manifest(&manifest, &item);
+
+        manifest.item.icon = self.resolve_repo_asset(&item, &item.icon);
+        manifest.item.cover = self.resolve_repo_asset(&item, &manifest.item.cover);
+        manifest.item.preview = manifest
+            .item
+            .preview
+            .iter()
+            .map(|path| self.resolve_repo_asset(&item, path))
+            .collect();
+        for link in manifest.links.iter_mut() {
+            if let Some(icon) = link.icon.as_mut() {
+                *icon = self.resolve_repo_asset(&item, icon);
+            }
+        }
+        for (device_id, download) in manifest.downloads.iter_mut() {
+            download.display_name = self.device_map_id_to_name(device_id);
+            let path = download.url.as_deref().unwrap_or(&download.file_name);
+            download.url = Some(self.resolve_repo_asset(&item, path));
+        }
+        manifest.item.paid_type = Some(item.paid_type.clone());
+        manifest.item.tags = non_empty_values(&item.tags);
+        manifest.item.devices = non_empty_values(&item.devices);
+        if let Some(lang) = self.preferred_language.load_full().as_deref() {
+            manifest.localize(lang);
+        }
+        Ok(manifest)
+    }
+
+    // 同 CommunityProvider::refresh；force 为 true 时忽略冷却时间
+    pub async fn refresh_with(&self, cfg: &str, force: bool) -> anyhow::Result<()> {
+        let parsed = RefreshConfig::parse(cfg)?;
+        self.refresh_parsed(&parsed, force).await
+    }
+
+    async fn refresh_parsed(&self, cfg: &RefreshConfig, force: bool) -> anyhow::Result<()> {
+        let span = tracing::info_span!(
+            "refresh",
+            offline = self.is_offline(),
+            cdn = tracing::field::Empty,
+            items = tracing::field::Empty,
+        );
+        let result = self
+            .refresh_inner(cfg, force)
+            .instrument(span.clone())
+            .await;
+        span.record("cdn", tracing::field::debug(*self.cdn.load_full()));
+        span.record("items", self.index.load().list.len());
+        if let Err(err) = &result {
+            span.in_scope(|| tracing::error!(error = %format!("{err:#}"), "refresh failed"));
+        }
+        result
+    }
+
+    async fn refresh_inner(&self, parsed: &RefreshConfig, force: bool) -> anyhow::Result<()> {
+        // 冷却期内以相同配置重复调用时不联网，报 ProviderError::Cooldown 供调用方识别（上次结果见 state()）；
+        // 配置变化或 force 时照常刷新
+        let remaining = self.refresh_cooldown_remaining();
+        if !force && *parsed == **self.last_refresh_cfg.load() && !remaining.is_zero() {
+            log::debug!("[OfficialV2] refresh called during cooldown, {remaining:?} left");
+            return Err(ProviderError::Cooldown(remaining).into());
+        }
+        self.mark_refresh_started();
+        self.set_state(ProviderState::Updating);
+        self.refreshing.store(true, Ordering::SeqCst);
+        self.last_refresh_cfg.store(Arc::new(parsed.clone()));
+
+        if self.is_offline() {
+            let loaded = self.load_cached_state().await;
+            self.refreshing.store(false, Ordering::SeqCst);
+            return match loaded {
+                Ok(true) => Ok(()),
+                Ok(false) => {
+                    self.set_state(ProviderState::Failed(ProviderError::Offline.to_string()));
+                    Err(anyhow::Error::new(ProviderError::Offline)
+                        .context("no cached state available"))
+                }
+                Err(err) => {
+                    self.set_state(ProviderState::Failed(format!("{err:#}")));
+                    Err(err)
+                }
+            };
+        }
+
+        let result = self.refresh_remote(parsed).await;
+        self.refreshing.store(false, Ordering::SeqCst);
+        if let Err(err) = &result {
+            // 冷启动且拉取失败时，退回上次成功刷新时落盘的数据
+            if self.index.load().list.is_empty() {
+                match self.load_cached_state().await {
+                    Ok(true) => {
+                        log::warn!("[OfficialV2] refresh failed, using cached state: {err}")
+                    }
+                    Ok(false) => {}
+                    Err(cache_err) => {
+                        log::warn!("[OfficialV2] failed to load cached state: {cache_err}")
+                    }
+                }
+            }
+        }
+
+        result
+    }
+
+    // 共享产物路径；未开启去重、manifest 未提供合法 sha256 或存储不落盘时为 None
+    fn blob_path(&self, resolved: &ResolvedDownload) -> Option<PathBuf> {
+        if !self.dedup_blobs.load(Ordering::SeqCst) {
+            return None;
+        }
+        let sha256 = resolved
+            .entry
+            .sha256
+            .as_deref()?
+            .trim()
+            .to_ascii_lowercase();
+        if sha256.len() != 64 || !sha256.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        self.store.local_path(&format!("{BLOB_CACHE_DIR}/{sha256}"))
+    }
+
+    // 共享区已有同一文件且校验通过时，链接/复制到资源目录；校验失败的 blob 会被删除
+    async fn reuse_blob(
+        &self,
+        resolved: &ResolvedDownload,
+        blob_path: &std::path::Path,
+        target: &std::path::Path,
+    ) -> bool {
+        let Ok(mut file) = File::open(blob_path).await else {
+            return false;
+        };
+        let expected = resolved
+            .entry
+            .sha256
+            .as_deref()
+            .map(|v| v.trim().to_ascii_lowercase());
+        match sha256_file(&mut file, blob_path).await {
+            Ok(actual) if Some(actual.as_str()) == expected.as_deref() => {}
+            _ => {
+                drop(file);
+                let _ = fs::remove_file(blob_path).await;
+                return false;
+            }
+        }
+        drop(file);
+        match link_or_copy(blob_path, target).await {
+            Ok(()) => {
+                log::info!(
+                    "[OfficialV2] reused cached blob for {} ({})",
+                    resolved.item.id,
+                    resolved.file_name
+                );
+                true
+            }
+            Err(err) => {
+                log::warn!(
+                    "[OfficialV2] failed to reuse blob {}: {err:#}",
+                    blob_path.display()
+                );
+                false
+            }
+        }
+    }
+
+    async fn download_inner(
+        &self,
+        item_id: String,
+        device: String,
+        progress_cb: Option<Box<dyn Fn(ProgressData) + Send>>,
+    ) -> anyhow::Result<std::path::PathBuf> {
+        let resolved = self.resolve_download(&item_id, &device).await?;
+        let final_path = self.artifact_path(&resolved.item.id, &resolved.file_name)?;
+        let item_dir = final_path
+            .parent()
+            .map(PathBuf::from)
+            .ok_or_else(|| anyhow!("invalid cache path {}", final_path.display()))?;
+        fs::create_dir_all(&item_dir)
+            .await
+            .with_context(|| format!("failed to create cache directory {}", item_dir.display()))?;
+
+        let blob_path = self.blob_path(&resolved);
+        if let Some(blob_path) = &blob_path
+            && self.reuse_blob(&resolved, blob_path, &final_path).await
+        {
+            if let Some(cb) = progress_cb.as_ref() {
+                cb(ProgressData {
+                    progress: 1.0,
+                    status: "finished".into(),
+                });
+            }
+            return Ok(final_path);
+        }
+
+        if let Some(cb) = progress_cb.as_ref() {
+            cb(ProgressData {
+                progress: 0.0,
+                status: "".into(),
+            });
+        }
+        let response = self.open_download(&resolved).await?;
+        let opts = DownloadOptions {
+            file_name: Some(resolved.file_name.clone()),
+            expected_sha256: resolved.entry.sha256.clone(),
+            buffer_size: Some(self.write_buffer_size.load(Ordering::SeqCst)),
+            ..Default::default()
+        };
+        let downloaded = download::save_response(response, &final_path, opts, progress_cb).await?;
+        tracing::Span::current().record("bytes", downloaded.size);
+
+        // 已通过 sha256 校验，登记到共享区；失败不影响本次下载
+        if let Some(blob_path) = &blob_path
+            && let Err(err) = link_or_copy(&final_path, blob_path).await
+        {
+            log::warn!(
+                "[OfficialV2] failed to store blob {}: {err:#}",
+                blob_path.display()
+            );
+        }
+
+        Ok(final_path)
+    }
+}
+
+#[async_trait]
+impl CommunityProvider for OfficialV2Provider {
+    fn provider_name(&self) -> String {
+        PROVIDER_NAME.to_string()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn into_any_arc(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
+        self
+    }
+    fn state(&self) -> ProviderState {
+        self.state.borrow().clone()
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        ProviderCapabilities {
+            supports_search: true,
+            supports_categories: true,
+            supports_download: true,
+            supports_update_check: true, // check_update / check_updates
+            paginated: true,
+        }
+    }
+
+    // 新订阅者的首次 changed() 立即返回当前状态
+    fn subscribe_state(&self) -> watch::Receiver<ProviderState> {
+        let mut rx = self.state.subscribe();
+        rx.mark_changed();
+        rx
+    }
+
+    fn export_config(&self) -> serde_json::Value {
+        let repo = self.repo.load();
+        let cfg = RefreshConfig {
+            cdn: Some(*self.cdn.load_full()),
+            repo_owner: Some(repo.owner.clone()),
+            repo_name: Some(repo.name.clone()),
+            repo_ref: Some(repo.reference.clone()),
+            raw_base_url: (repo.raw_base != DEFAULT_RAW_BASE).then(|| repo.raw_base.clone()),
+            preferred_language: (*self.preferred_language.load_full()).clone(),
+            verify_checksums: self.verify_checksums.load(Ordering::SeqCst),
+        };
+        serde_json::to_value(cfg).unwrap_or_default()
+    }
+
+    // 只更新设置，不联网；同时作为之后自动刷新使用的配置
+    fn apply_config(&self, cfg: serde_json::Value) -> anyhow::Result<()> {
+        let parsed: RefreshConfig = serde_json::from_value(cfg.clone())
+            .with_context(|| format!("invalid config: {cfg}"))?;
+        self.cdn
+            .store(Arc::new(parsed.cdn.unwrap_or(GitHubCdn::Raw)));
+        self.repo.store(Arc::new(RepoSource::from_config(&parsed)));
+        self.set_preferred_language(parsed.preferred_language.clone());
+        self.verify_checksums
+            .store(parsed.verify_checksums, Ordering::SeqCst);
+        self.last_refresh_cfg.store(Arc::new(parsed));
+        Ok(())
+    }
+
+    async fn refresh(&self, cfg: &str) -> anyhow::Result<()> {
+        self.refresh_with(cfg, false).await
+    }
+
+    async fn get_page(
+        &self,
+        page: u32,
+        limit: u32,
+        search: SearchConfig,
+    ) -> anyhow::Result<Vec<ManifestItemV2>> {
+        Ok(self.get_page_v2(page, limit, search).await?.items)
+    }
+
+    async fn get_categories(&self) -> anyhow::Result<Vec<String>> {
+        let mut categories = vec![
+            HIDE_PAID.to_string(),
+            HIDE_FORCE_PAID.to_string(),
+            QUICK_APP.to_string(),
+            WATCHFACE.to_string(),
+        ];
+
+        let device_map = self.device_map.load();
+        device_map
+            .xiaomi
+            .values()
+            .collect::<Vec<_>>()
+            .iter()
+            .for_each(|xmdev| {
+                if !categories.contains(&xmdev.name) {
+                    categories.push(xmdev.name.clone());
+                }
+            });
+
+        // TODO: 在支持Vivo设备后也显示vivo设备的分类
+
+        Ok(categories)
+    }
+    async fn get_item_manifest(&self, item_id: String) -> anyhow::Result<ManifestV2> {
+        let target_item = self.index_item(&item_id);
+
+        if let Some(item) = &target_item {
+            let mut manifest = self
+                .get_manifest(&item.repo_owner, &item.repo_name, &item.repo_commit_hash)
+                .await?;
+            backfill_legacy_identity(&mut manifest, item);
+            warn_invalid_manifest(&manifest, item);
+            manifest.item.fill_preview_media();
+
+            for (device_id, download) in manifest.downloads.iter_mut() {
+                download.display_name = self.device_map_id_to_name(device_id);
+            }
+
+            let mut cover = self.resolve_repo_asset(item, &manifest.item.cover);
+            let mut preview = manifest
+                .item
+                .preview
+                .iter()
+                .map(|p| self.resolve_repo_asset(item, p))
+                .collect::<Vec<_>>();
+            // 视频与图片一样经 CDN 转换，但不内联
+            let mut preview_media = manifest
+                .item
+                .preview_media
+                .iter()
+                .map(|media| PreviewMediaV2 {
+                    kind: media.kind,
+                    url: self.resolve_repo_asset(item, &media.url),
+                })
+                .collect::<Vec<_>>();
+            let mut icon = self.resolve_repo_asset(item, &item.icon);
+            for link in manifest.links.iter_mut() {
+                if let Some(link_icon) = link.icon.as_mut() {
+                    *link_icon = self.resolve_repo_asset(item, link_icon);
+                }
+            }
+
+            // 官方镜像源：详情页图片同样经境内 CDN 内联为 base64
+            if self.cdn.load_full().uses_astrobox_source_cdn() {
+                let (owner, repo, commit) = (
+                    item.repo_owner.clone(),
+                    item.repo_name.clone(),
+                    item.repo_commit_hash.clone(),
+                );
+                let mut refs = Vec::new();
+                let rels = std::iter::once(item.icon.as_str())
+                    .chain(std::iter::once(manifest.item.cover.as_str()))
+                    .chain(manifest.item.preview.iter().map(|s| s.as_str()))
+                    .chain(
+                        manifest
+                            .item
+                            .preview_media
+                            .iter()
+                            .filter(|media| {
+                                media.kind == PreviewMediaKind::Image
+                                    && !manifest.item.preview.contains(&media.url)
+                            })
+                            .map(|media| media.url.as_str()),
+                    );
+                for rel in rels {
+                    if let Some(rel) = Self::relative_image_path(rel) {
+                        refs.push(ImageRef {
+                            id: item.id.clone(),
+                            owner: owner.clone(),
+                            repo: repo.clone(),
+                            commit: commit.clone(),
+                            rel,
+                        });
+                    }
+                }
+
+                let inlined = self.inline_images(refs).await;
+                if !inlined.is_empty() {
+                    let lookup = |rel: &str| -> Option<String> {
+                        let rel = Self::relative_image_path(rel)?;
+                        inlined
+                            .get(&Self::image_cos_key(&owner, &repo, &commit, &rel))
+                            .cloned()
+                    };
+                    if let Some(data) = lookup(&item.icon) {
+                        icon = data;
+                    }
+                    if let Some(data) = lookup(&manifest.item.cover) {
+                        cover = data;
+                    }
+                    preview = manifest
+                        .item
+                        .preview
+                        .iter()
+                        .zip(preview.into_iter())
+                        .map(|(rel, fallback)| lookup(rel).unwrap_or(fallback))
+                        .collect();
+                    for (media, raw) in preview_media
+                        .iter_mut()
+                        .zip(manifest.item.preview_media.iter())
+                    {
+                        if media.kind == PreviewMediaKind::Image
+                            && let Some(data) = lookup(&raw.url)
+                        {
+                            media.url = data;
+                        }
+                    }
+                }
+            }
+
+            let mut manifest = ManifestV2 {
+                item: ManifestItemV2 {
+                    icon,
+                    preview,
+                    preview_media,
+                    cover,
+                    paid_type: Some(item.paid_type.clone()),
+                    tags: non_empty_values(&item.tags),
+                    devices: non_empty_values(&item.devices),
+                    ..manifest.item
+                },
+                ..manifest
+            };
+            if let Some(lang) = self.preferred_language.load_full().as_deref() {
+                manifest.localize(lang);
+            }
+            Ok(manifest)
+        } else {
+            Err(anyhow::anyhow!("Item not found"))
+        }
+    }
+
+    async fn download(
+        &self,
+        item_id: String,
+        device: String,
+        progress_cb: Option<Box<dyn Fn(ProgressData) + Send>>,
+    ) -> anyhow::Result<std::path::PathBuf> {
+        let span = self.download_span(&item_id, &device);
+        let result = self
+            .download_inner(item_id, device, progress_cb)
+            .instrument(span.clone())
+            .await;
+        if let Err(err) = &result {
+            span.in_scope(|| tracing::error!(error = %format!("{err:#}"), "download failed"));
+        }
+        result
+    }
+    async fn get_total_items(&self) -> anyhow::Result<u64> {
+        Ok(self.index.load().list.len() as u64)
+    }
+
+    // 仅用内存索引拼装，与 get_page 的列表项一致，不逐个拉取 manifest
+    async fn get_items_by_ids(
+        &self,
+        ids: &[String],
+    ) -> anyhow::Result<Vec<Option<ManifestItemV2>>> {
+        let found = ids
+            .iter()
+            .filter_map(|id| self.index_item(id))
+            .collect::<Vec<_>>();
+        let built: HashMap<String, ManifestItemV2> = self
+            .build_page_items(&found)
+            .await
+            .into_iter()
+            .map(|item| (item.id.clone(), item))
+            .collect();
+
+        Ok(ids.iter().map(|id| built.get(id).cloned()).collect())
+    }
+
+    // 前缀命中优先，其次按资源数；名称与标签同名时合并
+    async fn suggest(&self, prefix: &str, limit: usize) -> anyhow::Result<Vec<String>> {
+        let prefix = prefix.trim().to_lowercase();
+        if prefix.is_empty() || limit == 0 {
+            return Ok(Vec::new());
+        }
+        let entries = self.suggestions.load();
+
+        // 前缀命中在有序 key 上二分定位，其余再线性扫描包含命中
+        let start = entries.partition_point(|entry| entry.key.as_str() < prefix.as_str());
+        let mut prefixed = entries[start..]
+            .iter()
+            .take_while(|entry| entry.key.starts_with(&prefix))
+            .collect::<Vec<_>>();
+        let mut containing = entries
+            .iter()
+            .filter(|entry| !entry.key.starts_with(&prefix) && entry.key.contains(&prefix))
+            .collect::<Vec<_>>();
+
+        prefixed.sort_by(|a, b| b.weight.cmp(&a.weight).then_with(|| a.key.cmp(&b.key)));
+        containing.sort_by(|a, b| b.weight.cmp(&a.weight).then_with(|| a.key.cmp(&b.key)));
+
+        Ok(prefixed
+            .into_iter()
+            .chain(containing)
+            .take(limit)
+            .map(|entry| entry.text.clone())
+            .collect())
+    }
+
+    async fn probe_download_size(
+        &self,
+        item_id: String,
+        device: String,
+    ) -> anyhow::Result<Option<u64>> {
+        self.ensure_online()?;
+        let entry = self.resolve_download_entry(item_id, device, false).await?;
+        let url = entry.url.clone().context("download url missing")?;
+        let client = self.http_client();
+        let resp = client.get(&url).send().await?.error_for_status()?;
+        Ok(resp.content_length())
+    }
+}
+
+// 统计标签：大小写不敏感去重（保留首次出现的写法），同一资源内重复只计一次，忽略空标签
+fn collect_tags(index: &[IndexV2]) -> Vec<TagInfo> {
+    let mut tags: Vec<TagInfo> = Vec::new();
+    let mut positions: HashMap<String, usize> = HashMap::new();
+    for item in index {
+        let mut seen = Vec::new();
+        for tag in item
+            .tags
+            .iter()
+            .map(|tag| tag.trim())
+            .filter(|tag| !tag.is_empty())
+        {
+            let key = tag.to_lowercase();
+            if seen.contains(&key) {
+                continue;
+            }
+            let pos = *positions.entry(key.clone()).or_insert_with(|| {
+                tags.push(TagInfo {
+                    name: tag.to_string(),
+                    count: 0,
+                });
+                tags.len() - 1
+            });
+            tags[pos].count += 1;
+            seen.push(key);
+        }
+    }
+    tags.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    tags
+}
+
+async fn sha256_file(file: &mut File, path: &std::path::Path) -> anyhow::Result<String> {
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file
+            .read(&mut buf)
+            .await
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+// 优先硬链接，文件系统不支持（跨卷、FAT 等）时退回复制；目标已存在时先删除
+async fn link_or_copy(src: &std::path::Path, dst: &std::path::Path) -> anyhow::Result<()> {
+    if let Some(dir) = dst.parent() {
+        fs::create_dir_all(dir)
+            .await
+            .with_context(|| format!("failed to create directory {}", dir.display()))?;
+    }
+    let _ = fs::remove_file(dst).await;
+    if fs::hard_link(src, dst).await.is_ok() {
+        return Ok(());
+    }
+    fs::copy(src, dst)
+        .await
+        .map(|_| ())
+        .with_context(|| format!("failed to copy {} -> {}", src.display(), dst.display()))
+}
+
+pub(crate) enum FetchFailure {
+    NotFound, // 服务端明确返回 404
+    Network,  // 连接、超时或 5xx/429 等可重试的错误
+    Fatal,    // 内容无法解析等，重试无意义
+}
+
+pub(crate) fn classify_fetch_error(err: &anyhow::Error) -> FetchFailure {
+    match err.downcast_ref::<ProviderError>() {
+        Some(ProviderError::Timeout(_)) => return FetchFailure::Network,
+        Some(ProviderError::ManifestTooLarge(_)) => return FetchFailure::Fatal,
+        _ => {}
+    }
+    let Some(req_err) = err.chain().find_map(|e| e.downcast_ref::<reqwest::Error>()) else {
+        return FetchFailure::Fatal;
+    };
+    match req_err.status() {
+        Some(StatusCode::NOT_FOUND) => FetchFailure::NotFound,
+        Some(status) if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS => {
+            FetchFailure::Network
+        }
+        Some(_) => FetchFailure::Fatal,
+        None if req_err.is_decode() => FetchFailure::Fatal,
+        None => FetchFailure::Network,
+    }
+}
+
+// 仓库内的相对路径（去掉 `./` 与开头的 `/`）；绝对 URL 与内联数据返回 None
+fn repo_relative_path(path: &str) -> Option<&str> {
+    const ABSOLUTE_PREFIXES: [&str; 6] = ["http://", "https://", "//", "data:", "blob:", "tauri:"];
+    if ABSOLUTE_PREFIXES
+        .iter()
+        .any(|prefix| path.starts_with(prefix))
+    {
+        return None;
+    }
+    let mut rel = path;
+    loop {
+        let next = rel.trim_start_matches('/');
+        let next = next.strip_prefix("./").unwrap_or(next);
+        if next == rel {
+            return Some(rel);
+        }
+        rel = next;
+    }
+}
+
+// 名称排序键：启用 pinyin-sort 时汉字替换为无声调拼音、其余字符转小写，
+// 中英文名称按拼音字母交错排列；未启用时沿用按字节比较
+#[cfg(feature = "pinyin-sort")]
+fn name_collation_key(name: &str) -> String {
+    use pinyin::ToPinyin;
+
+    let mut key = String::with_capacity(name.len() * 2);
+    for ch in name.chars() {
+        match ch.to_pinyin() {
+            Some(py) => key.push_str(py.plain()),
+            None => key.extend(ch.to_lowercase()),
+        }
+    }
+    key
+}
+
+#[cfg(not(feature = "pinyin-sort"))]
+fn name_collation_key(name: &str) -> String {
+    name.to_string()
+}
+
+fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+// app_cache_dir 不可用时的缓存根目录，见 from_app_handle
+pub fn default_fallback_cache_root() -> PathBuf {
+    std::env::temp_dir().join("astrobox_cache")
+}
+
+// 拉取 manifest 文本：声明或实际读取的长度超过上限时立即中止（ManifestTooLarge），
+// 连同读取在内超过 timeout 报 Timeout；非 2xx 状态保留为 reqwest 错误供 classify_fetch_error 区分
+async fn fetch_manifest_text(
+    client: &reqwest::Client,
+    url: &str,
+    limits: ManifestLimits,
+) -> anyhow::Result<String> {
+    let fetch = async {
+        let resp = client.get(url).send().await?.error_for_status()?;
+        if resp
+            .content_length()
+            .is_some_and(|len| len > limits.max_bytes as u64)
+        {
+            return Err(ProviderError::ManifestTooLarge(limits.max_bytes).into());
+        }
+        let mut body = Vec::new();
+        let mut stream = resp.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk?;
+            if body.len() + chunk.len() > limits.max_bytes {
+                return Err(ProviderError::ManifestTooLarge(limits.max_bytes).into());
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok::<_, anyhow::Error>(String::from_utf8_lossy(&body).into_owned())
+    };
+    tokio::time::timeout(limits.timeout, fetch)
+        .await
+        .map_err(|_| anyhow::Error::new(ProviderError::Timeout(url.to_string())))?
+}
+
+// 截取响应体开头（按字符），用于错误信息
+fn body_preview(body: &str) -> String {
+    const PREVIEW_CHARS: usize = 200;
+    let trimmed = body.trim_start();
+    match trimmed.char_indices().nth(PREVIEW_CHARS) {
+        Some((end, _)) => format!("{}…", &trimmed[..end]),
+        None => trimmed.to_string(),
+    }
+}
+
+fn manifest_disk_key(owner: &str, repo: &str, commit_hash: &str) -> String {
+    format!(
+        "{}/{}",
+        MANIFEST_CACHE_DIR,
+        sanitize_local_filename(&format!("{owner}_{repo}_{commit_hash}.json"))
+    )
+}
+
+// v1 manifest 没有 id 与资源类型，以索引行为准补齐
+fn backfill_legacy_identity(manifest: &mut ManifestV2, item: &IndexV2) {
+    if !manifest.legacy {
+        return;
+    }
+    if manifest.item.id.trim().is_empty() {
+        manifest.item.id = item.id.clone();
+    }
+    manifest.item.restype = item.restype.clone();
+}
+
+// 详情页只记录校验问题，缺少产物时照常展示（下载不可用）；需要严格校验时用 get_validated_manifest
+fn warn_invalid_manifest(manifest: &ManifestV2, item: &IndexV2) {
+    if let Err(err) = validate_manifest(manifest, Some(item)) {
+        log::warn!("[OfficialV2] {err}");
+    }
+}
+
+// 分号切分可能产生空值，对外输出前剔除
+fn non_empty_values(values: &[String]) -> Vec<String> {
+    values
+        .iter()
+        .filter(|tag| !tag.trim().is_empty())
+        .cloned()
+        .collect()
+}
+
+// 相关度：标签 Jaccard 占主导，同类型、同作者（仓库拥有者）、设备重合度作为加分
+fn related_score(target: &IndexV2, other: &IndexV2) -> f64 {
+    fn jaccard(a: &[String], b: &[String]) -> f64 {
+        let a = a
+            .iter()
+            .map(|v| v.trim().to_lowercase())
+            .filter(|v| !v.is_empty())
+            .collect::<HashSet<_>>();
+        let b = b
+            .iter()
+            .map(|v| v.trim().to_lowercase())
+            .filter(|v| !v.is_empty())
+            .collect::<HashSet<_>>();
+        let union = a.union(&b).count();
+        if union == 0 {
+            return 0.0;
+        }
+        a.intersection(&b).count() as f64 / union as f64
+    }
+
+    let tags = jaccard(&target.tags, &other.tags);
+    let devices = jaccard(&target.devices, &other.devices);
+    let mut score = tags * 3.0 + devices;
+    if score == 0.0 {
+        return 0.0; // 既无共同标签也无共同设备，不算相关
+    }
+    if target.restype == other.restype {
+        score += 0.5;
+    }
+    if target.repo_owner.eq_ignore_ascii_case(&other.repo_owner) {
+        score += 0.5;
+    }
+    score
+}
+
+fn build_suggestions(index: &[IndexV2], tags: &[TagInfo]) -> Vec<SuggestEntry> {
+    let mut entries: HashMap<String, SuggestEntry> = HashMap::new();
+    let names = index
+        .iter()
+        .map(|item| (item.name.trim(), 1))
+        .chain(tags.iter().map(|tag| (tag.name.as_str(), tag.count)));
+    for (text, weight) in names {
+        if text.is_empty() {
+            continue;
+        }
+        entries
+            .entry(text.to_lowercase())
+            .and_modify(|entry| entry.weight += weight)
+            .or_insert_with(|| SuggestEntry {
+                key: text.to_lowercase(),
+                text: text.to_string(),
+                weight,
+            });
+    }
+
+    let mut entries = entries.into_values().collect::<Vec<_>>();
+    entries.sort_by(|a, b| a.key.cmp(&b.key));
+    entries
+}
+
+// 按空白（含全角空格 U+3000）切分为小写搜索词；`"..."` 内为一个短语（内部空白压缩为单个空格），
+// 未闭合的引号延续到末尾
+pub(crate) fn split_search_terms(keyword: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    for (i, segment) in keyword.split('"').enumerate() {
+        if i % 2 == 1 {
+            let phrase = segment.split_whitespace().collect::<Vec<_>>().join(" ");
+            if !phrase.is_empty() {
+                terms.push(phrase.to_lowercase());
+            }
+        } else {
+            terms.extend(segment.split_whitespace().map(str::to_lowercase));
+        }
+    }
+    terms
+}
+
+// 搜索词（须已小写）是否出现在名称或任一标签中
+fn item_matches_term(item: &IndexV2, term: &str) -> bool {
+    item.name.to_lowercase().contains(term)
+        || item
+            .tags
+            .iter()
+            .any(|tag| tag.to_lowercase().contains(term))
+}
+
+// 合并多个搜索词在同一字段上的命中区间
+fn find_terms_ranges(haystack: &str, terms: &[String]) -> Vec<std::ops::Range<usize>> {
+    let mut ranges = terms
+        .iter()
+        .flat_map(|term| find_match_ranges(haystack, term))
+        .collect::<Vec<_>>();
+    ranges.sort_by_key(|range| range.start);
+
+    let mut merged: Vec<std::ops::Range<usize>> = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
+// 大小写不敏感地查找 needle（须已小写）在原文中的所有不重叠字节区间。
+// 逐字符比较小写形式，避免小写后字节长度变化导致区间错位
+fn find_match_ranges(haystack: &str, needle_lower: &str) -> Vec<std::ops::Range<usize>> {
+    let mut ranges = Vec::new();
+    if needle_lower.is_empty() {
+        return ranges;
+    }
+
+    let mut search_from = 0;
+    for (start, _) in haystack.char_indices() {
+        if start < search_from {
+            continue;
+        }
+        let mut needle = needle_lower.chars().peekable();
+        let mut end = start;
+        let mut matched = true;
+        for (offset, ch) in haystack[start..].char_indices() {
+            if needle.peek().is_none() {
+                break;
+            }
+            matched = ch.to_lowercase().all(|lower| needle.next() == Some(lower));
+            if !matched {
+                break;
+            }
+            end = start + offset + ch.len_utf8();
+        }
+        if matched && needle.peek().is_none() && end > start {
+            ranges.push(start..end);
+            search_from = end;
+        }
+    }
+    ranges
+}
+
+// 客户端已自行跟随重定向时无法逐跳改写，最终地址违反策略则报错，避免悄悄绕开所选 CDN
+fn check_followed_redirect(
+    policy: CdnRedirectPolicy,
+    cdn: GitHubCdn,
+    requested: &str,
+    landed: &reqwest::Url,
+) -> anyhow::Result<()> {
+    let requested_host = reqwest::Url::parse(requested)?
+        .host_str()
+        .map(str::to_string);
+    let violated = match policy {
+        CdnRedirectPolicy::Follow => false,
+        CdnRedirectPolicy::DenyCrossHost => landed.host_str() != requested_host.as_deref(),
+        CdnRedirectPolicy::ReapplyCdn => cdn.convert_url(landed.as_str()) != landed.as_str(),
+    };
+    if violated {
+        return Err(anyhow!(
+            "client followed redirect {requested} -> {landed} against {policy:?}; \
+             build the injected client with redirect::Policy::none()"
+        ));
+    }
+    Ok(())
+}
+
+// 索引 CSV 可能带 UTF-8 BOM 或混用 CRLF/CR 换行，先统一再交给 csv 解析，
+// 否则首列 id 会带上 BOM。引号内的换行属于字段内容，原样保留
+pub(crate) fn sanitize_index_csv(raw: &[u8]) -> String {
+    let raw = raw.strip_prefix(b"\xef\xbb\xbf").unwrap_or(raw);
+    let text = String::from_utf8_lossy(raw);
+    let mut normalized = String::with_capacity(text.len());
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            // 转义的 `""` 切换两次，状态不变
+            '"' => {
+                in_quotes = !in_quotes;
+                normalized.push(c);
+            }
+            '\r' if !in_quotes => {
+                chars.next_if_eq(&'\n');
+                normalized.push('\n');
+            }
+            _ => normalized.push(c),
+        }
+    }
+    strip_zero_width(&normalized)
+}
+
+fn is_checksum_mismatch(err: &anyhow::Error) -> bool {
+    matches!(
+        err.downcast_ref::<ProviderError>(),
+        Some(ProviderError::ChecksumMismatch(_))
+    )
+}
+
+fn strip_zero_width(input: &str) -> String {
+    input
+        .chars()
+        .filter(|c| {
+            !matches!(
+                *c,
+                '\u{200b}' | '\u{200c}' | '\u{200d}' | '\u{2060}' | '\u{feff}'
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, routing::get};
+
+    use super::*;
+    use crate::{
+        cache::MemoryCacheStore,
+        test_support::{index_item, serve},
+    };
+
+    const INDEX_CSV: &str = "\
+id,name,restype,repo_owner,repo_name,repo_commit_hash,icon,cover,tags,device_vendors,devices,paid_type
+w1,Black Face,watchface,o,w1,c1,icon.png,cover.png,黑色;表盘,xiaomi,o66,
+";
+    const DEVICES_JSON: &str = r#"{"xiaomi":{"o66":{"id":"o66","name":"Xiaomi Watch S4","description":"","chip":"xring","fetch":true}},"vivo":{}}"#;
+    const EXPLORE_JSON: &str = "{}";
+
+    fn provider() -> OfficialV2Provider {
+        OfficialV2Provider::new(GitHubCdn::Raw, Arc::new(MemoryCacheStore::new()))
+    }
+
+    fn search(filter: &str) -> SearchConfig {
+        SearchConfig {
+            filter: Some(filter.into()),
+            sort: SortRuleV2::Name,
+            ..Default::default()
+        }
+    }
+
+    fn ids(list: &[IndexV2]) -> Vec<&str> {
+        list.iter().map(|item| item.id.as_str()).collect()
+    }
+
+    // 两个表盘与一个快应用，供搜索相关测试使用
+    fn search_provider() -> OfficialV2Provider {
+        let provider = provider();
+        provider.load_index(vec![
+            index_item("w1", "Black Face", &["小米", "表盘", "黑色"]),
+            index_item("w2", "White Face", &["小米", "表盘", "白色"]),
+            index_item("q1", "Calculator", &["小米", "工具"]),
+        ]);
+        provider
+    }
+
+    fn refresh_cfg(base: &str) -> String {
+        serde_json::json!({
+            "repo_owner": "o",
+            "repo_name": "r",
+            "repo_ref": "main",
+            "raw_base_url": base,
+        })
+        .to_string()
+    }
+
+    // 仓库根目录下的三个资源，failing 对应的那个返回 500
+    fn repo_router(index_csv: &'static str, failing: Option<&'static str>) -> Router {
+        let respond = move |file: &'static str, body: &'static str| {
+            if failing == Some(file) {
+                (StatusCode::INTERNAL_SERVER_ERROR, "boom")
+            } else {
+                (StatusCode::OK, body)
+            }
+        };
+        Router::new()
+            .route(
+                "/o/r/main/index_v2.csv",
+                get(move || async move { respond("index_v2.csv", index_csv) }),
+            )
+            .route(
+                "/o/r/main/devices_v2.json",
+                get(move || async move { respond("devices_v2.json", DEVICES_JSON) }),
+            )
+            .route(
+                "/o/r/main/explore_v2.json",
+                get(move || async move { respond("explore_v2.json", EXPLORE_JSON) }),
+            )
+    }
+
+    #[tokio::test]
+    async fn refresh_ready_when_all_resources_load() {
+        let base = serve(repo_router(INDEX_CSV, None)).await;
+        let provider = provider();
+        provider.refresh(&refresh_cfg(&base)).await.unwrap();
+        assert!(matches!(provider.state(), ProviderState::Ready));
+        assert!(provider.has_item("w1"));
+        assert!(provider.device_map_id_to_name("o66").is_some());
+    }
+
+    #[tokio::test]
+    async fn refresh_fails_when_index_errors() {
+        let base = serve(repo_router(INDEX_CSV, Some("index_v2.csv"))).await;
+        let provider = provider();
+        let err = provider.refresh(&refresh_cfg(&base)).await.unwrap_err();
+        assert!(format!("{err:#}").contains("500"));
+        match provider.state() {
+            ProviderState::Failed(message) => {
+                assert!(message.contains("索引加载失败"), "{message}");
+                assert!(message.contains("index_v2.csv"), "{message}");
+            }
+            state => panic!("unexpected state {state:?}"),
+        }
+        assert!(!provider.has_item("w1"));
+    }
+
+    #[tokio::test]
+    async fn refresh_partial_when_devices_or_explore_error() {
+        for (failing, label) in [
+            ("devices_v2.json", "设备列表加载失败"),
+            ("explore_v2.json", "探索页加载失败"),
+        ] {
+            let base = serve(repo_router(INDEX_CSV, Some(failing))).await;
+            let provider = provider();
+            assert!(provider.refresh(&refresh_cfg(&base)).await.is_err());
+            match provider.state() {
+                ProviderState::Partial(message) => {
+                    assert!(message.contains(label), "{message}");
+                    assert!(message.contains(failing), "{message}");
+                }
+                state => panic!("unexpected state {state:?} when {failing} fails"),
+            }
+            // 索引已加载，商店仍可用
+            assert!(provider.has_item("w1"));
+        }
+    }
+
+    // /a 302 到 location，/b 返回正文
+    fn redirect_router(location: String) -> Router {
+        Router::new()
+            .route(
+                "/a",
+                get(move || async move {
+                    (
+                        StatusCode::FOUND,
+                        [(axum::http::header::LOCATION, location)],
+                    )
+                }),
+            )
+            .route("/b", get(|| async { "artifact" }))
     }
 
-    async fn get_categories(&self) -> anyhow::Result<Vec<String>> {
-        let mut categories = vec![
-            HIDE_PAID.to_string(),
-            HIDE_FORCE_PAID.to_string(),
-            QUICK_APP.to_string(),
-            WATCHFACE.to_string(),
-        ];
+    fn mirror_provider(policy: CdnRedirectPolicy) -> OfficialV2Provider {
+        let provider =
+            OfficialV2Provider::new(GitHubCdn::GhProxy, Arc::new(MemoryCacheStore::new()));
+        provider.set_redirect_policy(policy);
+        provider
+    }
 
-        let device_map = self.device_map.load();
-        device_map
-            .xiaomi
-            .values()
-            .collect::<Vec<_>>()
-            .iter()
-            .for_each(|xmdev| {
-                if !categories.contains(&xmdev.name) {
-                    categories.push(xmdev.name.clone());
-                }
-            });
+    #[tokio::test]
+    async fn download_refuses_redirect_to_raw_origin() {
+        let base = serve(redirect_router(
+            "https://raw.githubusercontent.com/o/r/c/face.bin".into(),
+        ))
+        .await;
+        let provider = mirror_provider(CdnRedirectPolicy::DenyCrossHost);
+        let err = provider
+            .send_download_request(&format!("{base}/a"))
+            .await
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("refused cross-host redirect"));
+    }
 
-        // TODO: 在支持Vivo设备后也显示vivo设备的分类
+    #[tokio::test]
+    async fn download_follows_same_host_redirect() {
+        let base = serve(redirect_router("/b".into())).await;
+        let provider = mirror_provider(CdnRedirectPolicy::DenyCrossHost);
+        let response = provider
+            .send_download_request(&format!("{base}/a"))
+            .await
+            .unwrap();
+        assert_eq!(response.text().await.unwrap(), "artifact");
+    }
 
-        Ok(categories)
+    #[tokio::test]
+    async fn injected_client_that_follows_redirects_is_checked() {
+        let router = Router::new().route("/b", get(|| async { "artifact" }));
+        let base = serve(router).await;
+        let port = base.rsplit(':').next().unwrap().to_string();
+        let other_host = serve(redirect_router(format!("http://localhost:{port}/b"))).await;
+
+        let provider = OfficialV2Provider::with_client(
+            GitHubCdn::GhProxy,
+            Arc::new(MemoryCacheStore::new()),
+            reqwest::Client::new(),
+        );
+        provider.set_redirect_policy(CdnRedirectPolicy::DenyCrossHost);
+        let err = provider
+            .send_download_request(&format!("{other_host}/a"))
+            .await
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("Policy::none()"));
+
+        // Follow 策略下照常使用客户端跟随后的响应
+        provider.set_redirect_policy(CdnRedirectPolicy::Follow);
+        let response = provider
+            .send_download_request(&format!("{other_host}/a"))
+            .await
+            .unwrap();
+        assert_eq!(response.text().await.unwrap(), "artifact");
     }
-    async fn get_item_manifest(&self, item_id: String) -> anyhow::Result<ManifestV2> {
-        let index = self.index.load().clone();
-        let target_item = index.iter().find(|item| item.id == item_id);
 
-        if let Some(item) = target_item {
-            let mut manifest = self
-                .get_manifest(&item.repo_owner, &item.repo_name, &item.repo_commit_hash)
-                .await?;
+    const INDEX_WITH_BAD_ROW: &str = "\
+id,name,restype,repo_owner,repo_name,repo_commit_hash,icon,cover,tags,device_vendors,devices,paid_type
+w1,Black Face,watchface,o,w1,c1,icon.png,cover.png,,,,
+w2,Broken,not_a_type,o,w2,c2,icon.png,cover.png,,,,
+w3,White Face,watchface,o,w3,c3,icon.png,cover.png,,,,
+";
+
+    #[test]
+    fn parse_index_skips_corrupt_middle_row() {
+        let (list, report) = provider().parse_index(INDEX_WITH_BAD_ROW).unwrap();
+        let ids = list.iter().map(|item| item.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, ["w1", "w3"]);
+        assert_eq!(report.parsed_rows, 2);
+        assert_eq!(report.skipped_rows.len(), 1);
+        assert_eq!(report.skipped_rows[0].row, 3);
+        assert!(!report.skipped_rows[0].reason.is_empty());
+    }
 
-            for (device_id, download) in manifest.downloads.iter_mut() {
-                download.display_name = self.device_map_id_to_name(device_id);
-            }
+    #[tokio::test]
+    async fn refresh_keeps_rows_around_a_corrupt_one() {
+        let base = serve(repo_router(INDEX_WITH_BAD_ROW, None)).await;
+        let provider = provider();
+        provider.refresh(&refresh_cfg(&base)).await.unwrap();
+        assert!(provider.has_item("w1"));
+        assert!(!provider.has_item("w2"));
+        assert!(provider.has_item("w3"));
+        assert_eq!(provider.last_refresh_report().skipped_rows.len(), 1);
+    }
 
-            let base = self.build_repo_cdn_url_by_index_item(item);
-            let mut cover = self.resolve_repo_asset_url(&base, &manifest.item.cover);
-            let mut preview = manifest
-                .item
-                .preview
-                .iter()
-                .map(|p| self.resolve_repo_asset_url(&base, p))
-                .collect::<Vec<_>>();
-            let mut icon = self.resolve_repo_asset_url(&base, &item.icon);
+    #[tokio::test]
+    async fn refresh_fails_when_no_row_parses() {
+        const ALL_BAD: &str = "\
+id,name,restype,repo_owner,repo_name,repo_commit_hash,icon,cover,tags,device_vendors,devices,paid_type
+w2,Broken,not_a_type,o,w2,c2,icon.png,cover.png,,,,
+";
+        let base = serve(repo_router(ALL_BAD, None)).await;
+        let err = provider().refresh(&refresh_cfg(&base)).await.unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ProviderError>(),
+            Some(ProviderError::EmptyIndex(1))
+        ));
+    }
 
-            // 官方镜像源：详情页图片同样经境内 CDN 内联为 base64
-            if self.cdn.load_full().uses_astrobox_source_cdn() {
-                let (owner, repo, commit) = (
-                    item.repo_owner.clone(),
-                    item.repo_name.clone(),
-                    item.repo_commit_hash.clone(),
-                );
-                let mut refs = Vec::new();
-                let rels = std::iter::once(item.icon.as_str())
-                    .chain(std::iter::once(manifest.item.cover.as_str()))
-                    .chain(manifest.item.preview.iter().map(|s| s.as_str()));
-                for rel in rels {
-                    if let Some(rel) = Self::relative_image_path(rel) {
-                        refs.push(ImageRef {
-                            id: item.id.clone(),
-                            owner: owner.clone(),
-                            repo: repo.clone(),
-                            commit: commit.clone(),
-                            rel,
-                        });
-                    }
-                }
+    #[test]
+    fn sanitize_index_csv_strips_bom_and_normalizes_line_endings() {
+        let raw = "\u{feff}id,name,restype,repo_owner,repo_name,repo_commit_hash,icon,cover,tags,device_vendors,devices,paid_type\r\n\
+w1,Black Face,watchface,o,w1,c1,icon.png,cover.png,,,,\r\
+w2,White Face,watchface,o,w2,c2,icon.png,cover.png,,,,\r\n";
+        let sanitized = sanitize_index_csv(raw.as_bytes());
+        assert!(!sanitized.contains('\r'));
+        let (list, report) = provider().parse_index(&sanitized).unwrap();
+        assert!(report.skipped_rows.is_empty());
+        let ids = list.iter().map(|item| item.id.as_str()).collect::<Vec<_>>();
+        assert_eq!(ids, ["w1", "w2"]);
+    }
 
-                let inlined = self.inline_images(refs).await;
-                if !inlined.is_empty() {
-                    let lookup = |rel: &str| -> Option<String> {
-                        let rel = Self::relative_image_path(rel)?;
-                        inlined
-                            .get(&Self::image_cos_key(&owner, &repo, &commit, &rel))
-                            .cloned()
-                    };
-                    if let Some(data) = lookup(&item.icon) {
-                        icon = data;
-                    }
-                    if let Some(data) = lookup(&manifest.item.cover) {
-                        cover = data;
-                    }
-                    preview = manifest
-                        .item
-                        .preview
-                        .iter()
-                        .zip(preview.into_iter())
-                        .map(|(rel, fallback)| lookup(rel).unwrap_or(fallback))
-                        .collect();
-                }
-            }
+    #[test]
+    fn sanitize_index_csv_keeps_line_breaks_inside_quotes() {
+        let raw = b"id,name\r\nw1,\"line one\rline two\r\nline \"\"three\"\"\"\r\nw2,plain\r";
+        assert_eq!(
+            sanitize_index_csv(raw),
+            "id,name\nw1,\"line one\rline two\r\nline \"\"three\"\"\"\nw2,plain\n"
+        );
+    }
 
-            Ok(ManifestV2 {
-                item: ManifestItemV2 {
-                    icon,
-                    preview,
-                    cover,
-                    paid_type: Some(item.paid_type.clone()),
-                    ..manifest.item
-                },
-                ..manifest
-            })
-        } else {
-            Err(anyhow::anyhow!("Item not found"))
-        }
+    #[test]
+    fn keyword_terms_narrow_results() {
+        let provider = search_provider();
+        let filter = |keyword: &str| provider.filter_index(&search(keyword));
+        assert_eq!(ids(&filter("表盘")), ["w1", "w2"]);
+        assert_eq!(ids(&filter("小米 表盘")), ["w1", "w2"]);
+        assert_eq!(ids(&filter("小米 表盘 黑色")), ["w1"]);
+        assert!(filter("小米 工具 表盘").is_empty());
     }
 
-    async fn download(
-        &self,
-        item_id: String,
-        device: String,
-        progress_cb: Option<Box<dyn Fn(ProgressData) + Send>>,
-    ) -> anyhow::Result<std::path::PathBuf> {
-        let index = self.index.load();
-        let index_ref = index.clone();
+    #[test]
+    fn keyword_split_handles_full_width_spaces() {
+        let provider = search_provider();
+        assert_eq!(
+            ids(&provider.filter_index(&search("小米\u{3000}表盘\u{3000}白色"))),
+            ["w2"]
+        );
+    }
 
-        // 优先根据id查找，找不到再跟名称
-        // 这是为了兼容v1的manifest无id
-        let item = index_ref
+    #[tokio::test]
+    async fn related_items_are_scored_deterministically() {
+        let provider = provider();
+        let mut tool = index_item("q1", "Calculator", &["小米", "工具"]);
+        tool.restype = ResourceTypeV2::QuickApp;
+        provider.load_index(vec![
+            index_item("w1", "Black Face", &["小米", "表盘", "黑色"]),
+            index_item("x1", "Unrelated", &["其他"]),
+            tool,
+            index_item("w2", "White Face", &["小米", "表盘", "白色"]),
+        ]);
+
+        let related = provider.get_related_items("w1", 10).await.unwrap();
+        let related_ids = related
             .iter()
-            .find(|entry| entry.id == item_id)
-            .or_else(|| index_ref.iter().find(|entry| entry.name == item_id))
-            .cloned()
-            .ok_or_else(|| anyhow!("Item not found by id or name"))?;
+            .map(|item| item.id.as_str())
+            .collect::<Vec<_>>();
+        // w2：标签 2/4 * 3 + 同类型 + 同作者；q1：标签 1/4 * 3 + 同作者；x1 无共同标签或设备
+        assert_eq!(related_ids, ["w2", "q1"]);
+        assert!(related[0].icon.ends_with("/o/w2/c/icon.png"));
 
-        let manifest = self
-            .get_manifest(&item.repo_owner, &item.repo_name, &item.repo_commit_hash)
+        let top = provider.get_related_items("w1", 1).await.unwrap();
+        assert_eq!(top.len(), 1);
+        assert_eq!(top[0].id, "w2");
+    }
+
+    #[tokio::test]
+    async fn related_items_of_unknown_id_error_like_manifest() {
+        let provider = search_provider();
+        let related = provider.get_related_items("missing", 5).await.unwrap_err();
+        let manifest = provider
+            .get_item_manifest("missing".into())
             .await
-            .with_context(|| format!("failed to fetch manifest for {}", item.name))?;
+            .unwrap_err();
+        assert_eq!(related.to_string(), manifest.to_string());
+    }
 
-        let downloads = &manifest.downloads;
-        let (resolved_device, download_entry) = downloads
-            .get(&device)
-            .map(|entry| (device.as_str(), entry))
-            .or_else(|| downloads.get("default").map(|entry| ("default", entry)))
-            .or_else(|| downloads.iter().next().map(|(key, entry)| (key.as_str(), entry)))
-            .map(|(key, entry)| (key.to_string(), entry.clone()))
-            .ok_or_else(|| anyhow!("no downloadable artifact for device `{device}`"))?;
+    #[tokio::test]
+    async fn get_page_rejects_zero_limit() {
+        let provider = search_provider();
+        assert!(provider.get_page_v2(0, 0, search("")).await.is_err());
+    }
 
-        let mut file_name = download_entry.file_name.trim().to_string();
-        if file_name.is_empty() {
-            if let Some(url) = &download_entry.url {
-                if let Some(name) = url.split('/').last() {
-                    file_name = name.to_string();
-                }
-            }
-        }
-        if file_name.is_empty() {
-            return Err(anyhow!("download entry missing file name"));
-        }
+    #[tokio::test]
+    async fn get_page_with_limit_one() {
+        let provider = search_provider();
+        let page = provider.get_page_v2(1, 1, search("")).await.unwrap();
+        assert_eq!(page.items.len(), 1);
+        assert_eq!(page.items[0].id, "q1"); // 按名称升序：Black Face, Calculator, White Face
+        assert_eq!(page.limit, 1);
+        assert_eq!(page.total_items, 3);
+        assert_eq!(page.total_pages, 3);
+        assert!(page.has_next);
+    }
 
-        let safe_file_name = sanitize_local_filename(&file_name);
+    #[tokio::test]
+    async fn get_page_with_limit_larger_than_index() {
+        let provider = search_provider();
+        let page = provider.get_page_v2(0, 1000, search("")).await.unwrap();
+        assert_eq!(page.items.len(), 3);
+        assert_eq!(page.limit, 1000);
+        assert_eq!(page.total_pages, 1);
+        assert!(!page.has_next);
+
+        let past_end = provider.get_page_v2(1, 1000, search("")).await.unwrap();
+        assert!(past_end.items.is_empty());
+        assert_eq!(past_end.limit, 1000);
+
+        let huge = provider
+            .get_page_v2(u32::MAX, u32::MAX, search(""))
+            .await
+            .unwrap();
+        assert!(huge.items.is_empty());
+        assert_eq!(huge.limit, u32::MAX);
+    }
 
-        let cdn = *self.cdn.load_full();
-        let resolved_url = if cdn.uses_astrobox_source_cdn() {
-            self.resolve_source_cdn_download_url(&item.id, Some(&resolved_device))
-                .await?
-        } else if let Some(url) = &download_entry.url {
-            cdn.convert_url(url)
-        } else {
-            format!(
-                "{}/{}",
-                self.build_repo_cdn_url_by_index_item(&item),
-                &file_name
+    #[test]
+    fn keyword_match_ignores_latin_case() {
+        let provider = provider();
+        provider.load_index(vec![
+            index_item("p1", "Pixel Minimal", &["Google"]),
+            index_item("p2", "pixel dark", &["AOD"]),
+            index_item("w1", "Black Face", &["表盘"]),
+        ]);
+        let filter = |keyword: &str| provider.filter_index(&search(keyword));
+        assert_eq!(ids(&filter("pixel")), ["p1", "p2"]);
+        assert_eq!(ids(&filter("PIXEL")), ["p1", "p2"]);
+        assert_eq!(ids(&filter("pIxEl MINIMAL")), ["p1"]);
+        assert_eq!(ids(&filter("google")), ["p1"]);
+        assert_eq!(ids(&filter("aod")), ["p2"]);
+    }
+
+    #[test]
+    fn keyword_match_cjk_with_mixed_case_latin() {
+        let provider = provider();
+        provider.load_index(vec![
+            index_item("w1", "小米 Watch 表盘", &["黑色"]),
+            index_item("w2", "小米手环表盘", &["白色"]),
+        ]);
+        let filter = |keyword: &str| provider.filter_index(&search(keyword));
+        assert_eq!(ids(&filter("表盘")), ["w1", "w2"]);
+        assert_eq!(ids(&filter("小米 WATCH")), ["w1"]);
+        assert_eq!(ids(&filter("watch 黑色")), ["w1"]);
+        assert!(filter("表盘 蓝色").is_empty());
+    }
+
+    #[test]
+    fn search_index_agrees_with_linear_scan() {
+        let list = vec![
+            index_item("w1", "小米手环表盘", &["Black Face"]),
+            index_item("w2", "Pixel Minimal", &["表盘", "AOD"]),
+            index_item("w3", "calculator", &["工具"]),
+        ];
+        let search = SearchIndex::build(&list);
+        for term in [
+            "表盘", "手环", "ack", "pixel", "imal", "aod", "c", "x", "缺失",
+        ] {
+            let mut indexed = search.substring_hits(term).into_iter().collect::<Vec<_>>();
+            indexed.sort_unstable();
+            let scanned = (0..list.len())
+                .filter(|pos| item_matches_term(&list[*pos], term))
+                .collect::<Vec<_>>();
+            assert_eq!(indexed, scanned, "term `{term}`");
+        }
+    }
+
+    // v1 manifest 缺少 id 与资源类型，详情页读取时以索引补齐；缺少产物只记录警告
+    #[tokio::test]
+    async fn legacy_manifest_loads_without_downloads() {
+        let provider = provider();
+        provider.load_index(vec![index_item("w1", "Black Face", &[])]);
+        let legacy = crate::community::legacyparse::manifest_v1_to_v2(serde_json::json!({
+            "item": { "name": "Black Face", "description": "", "preview": [], "author": [] },
+            "downloads": {},
+        }))
+        .unwrap();
+        provider
+            .store
+            .write(
+                &manifest_disk_key("o", "w1", "c"),
+                &serde_json::to_vec(&legacy).unwrap(),
             )
-        };
+            .await
+            .unwrap();
 
-        let cache_root = self.cache_root()?;
-        let item_dir = cache_root.join(&item.id);
-        fs::create_dir_all(&item_dir)
+        let manifest = provider.get_item_manifest("w1".into()).await.unwrap();
+        assert_eq!(manifest.item.id, "w1");
+        assert_eq!(manifest.item.restype, ResourceTypeV2::WatchFace);
+        assert!(provider.get_resolved_manifest("w1").await.is_ok());
+
+        let err = provider
+            .get_validated_manifest("w1".into())
             .await
-            .with_context(|| format!("failed to create cache directory {}", item_dir.display()))?;
+            .unwrap_err();
+        assert!(format!("{err:#}").contains("downloads"));
+    }
 
-        let final_path = item_dir.join(&safe_file_name);
-        let unique_suffix = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_nanos();
-        let tmp_path = item_dir.join(format!("{}.{}.part", unique_suffix, safe_file_name));
-        let client = crate::net::default_client();
-        let cleanup_path = tmp_path.clone();
-        let download_result = {
-            let resolved_url = resolved_url;
-            let final_path = final_path;
-            let tmp_path = tmp_path;
-            let progress_cb = progress_cb;
-            async move {
-                let mut file = File::create(&tmp_path).await.with_context(|| {
-                    format!("failed to create temp file {}", tmp_path.display())
-                })?;
-
-                if let Some(cb) = progress_cb.as_ref() {
-                    cb(ProgressData {
-                        progress: 0.0,
-                        status: "".into(),
-                    });
-                }
+    // manifest 与旧版 manifest 都不存在时，错误里要有状态码与地址
+    #[tokio::test]
+    async fn manifest_not_found_reports_status_and_url() {
+        let base = serve(Router::new()).await;
+        let repo = format!("{base}/o/w1/c");
+        let err = OfficialV2Provider::fetch_manifest_from(
+            &reqwest::Client::new(),
+            &repo,
+            ManifestLimits::default(),
+        )
+        .await
+        .unwrap_err();
+        let message = format!("{err:#}");
+        assert!(message.contains("404"), "{message}");
+        assert!(
+            message.contains(&format!("{repo}/manifest.json")),
+            "{message}"
+        );
+        assert!(matches!(classify_fetch_error(&err), FetchFailure::NotFound));
+    }
 
-                let response = client
-                    .get(&resolved_url)
-                    .send()
-                    .await
-                    .with_context(|| format!("failed to request {}", resolved_url))?
-                    .error_for_status()
-                    .with_context(|| {
-                        format!("download request returned error for {}", resolved_url)
-                    })?;
-
-                let total = response.content_length();
-                let mut stream = response.bytes_stream();
-                let mut downloaded: u64 = 0;
-                let mut last_emit = Instant::now();
-                let step_bytes = total.map(|t| cmp::max(1, t / 100));
-                let mut last_reported = 0u64;
-
-                while let Some(chunk) = stream.next().await {
-                    let chunk = chunk.with_context(|| "failed to read download chunk")?;
-                    downloaded += chunk.len() as u64;
-                    file.write_all(chunk.as_ref())
-                        .await
-                        .with_context(|| "failed to write download chunk")?;
-
-                    if let Some(cb) = progress_cb.as_ref() {
-                        let mut emit = last_emit.elapsed() >= Duration::from_millis(200);
-                        if !emit {
-                            if let Some(step) = step_bytes {
-                                if downloaded >= last_reported.saturating_add(step)
-                                    || total.map(|t| downloaded >= t).unwrap_or(false)
-                                {
-                                    emit = true;
-                                }
-                            }
-                        }
+    // 镜像返回 HTML 时，解析错误附带响应开头
+    #[tokio::test]
+    async fn manifest_parse_failure_includes_body_preview() {
+        let router = Router::new().route(
+            "/o/w1/c/manifest_v2.json",
+            get(|| async { "<html>rate limited</html>" }),
+        );
+        let base = serve(router).await;
+        let repo = format!("{base}/o/w1/c");
+        let err = OfficialV2Provider::fetch_manifest_from(
+            &reqwest::Client::new(),
+            &repo,
+            ManifestLimits::default(),
+        )
+        .await
+        .unwrap_err();
+        let message = format!("{err:#}");
+        assert!(
+            message.contains(&format!("{repo}/manifest_v2.json")),
+            "{message}"
+        );
+        assert!(message.contains("<html>rate limited</html>"), "{message}");
+    }
 
-                        if emit {
-                            let progress = match total {
-                                Some(total_len) if total_len > 0 => {
-                                    (downloaded as f32 / total_len as f32).clamp(0.0, 1.0)
-                                }
-                                _ => 0.0,
-                            };
-                            cb(ProgressData {
-                                progress,
-                                status: "".into(),
-                            });
-                            last_emit = Instant::now();
-                            if step_bytes.is_some() {
-                                last_reported = downloaded;
-                            }
-                        }
-                    }
-                }
+    #[test]
+    fn resolve_repo_asset_handles_relative_and_absolute_paths() {
+        let provider = provider();
+        provider.set_cdn(GitHubCdn::GhFast);
+        let item = index_item("w1", "Black Face", &[]);
+        let repo = "https://ghfast.top/raw.githubusercontent.com/o/w1/c";
 
-                file.flush()
-                    .await
-                    .with_context(|| format!("failed to flush {}", tmp_path.display()))?;
+        assert_eq!(
+            provider.resolve_repo_asset(&item, "img/cover.png"),
+            format!("{repo}/img/cover.png")
+        );
+        assert_eq!(
+            provider.resolve_repo_asset(&item, "./img/cover.png"),
+            format!("{repo}/img/cover.png")
+        );
+        assert_eq!(
+            provider.resolve_repo_asset(&item, " /img/cover.png"),
+            format!("{repo}/img/cover.png")
+        );
+        // GitHub raw 地址经当前 CDN 转换，第三方地址原样保留
+        assert_eq!(
+            provider.resolve_repo_asset(&item, "https://raw.githubusercontent.com/x/y/main/a.png"),
+            "https://ghfast.top/raw.githubusercontent.com/x/y/main/a.png"
+        );
+        assert_eq!(
+            provider.resolve_repo_asset(&item, "https://example.com/a.png"),
+            "https://example.com/a.png"
+        );
+    }
 
-                drop(file);
+    #[test]
+    fn keyword_mode_any_unions_terms() {
+        let provider = search_provider();
+        let any = |keyword: &str| {
+            let mut config = search(keyword);
+            config.keyword_mode = KeywordMode::Any;
+            provider.filter_index(&config)
+        };
+        assert_eq!(ids(&any("黑色 工具")), ["w1", "q1"]);
+        assert_eq!(ids(&any("白色 不存在")), ["w2"]);
+        assert!(any("不存在 没有").is_empty());
+    }
 
-                fs::rename(&tmp_path, &final_path).await.with_context(|| {
-                    format!(
-                        "failed to move downloaded file {} -> {}",
-                        tmp_path.display(),
-                        final_path.display()
-                    )
-                })?;
+    #[test]
+    fn quoted_phrases_match_as_one_term() {
+        assert_eq!(
+            split_search_terms(r#"小米 "Black   Face" "unclosed  tail"#),
+            ["小米", "black face", "unclosed tail"]
+        );
 
-                if let Some(cb) = progress_cb.as_ref() {
-                    cb(ProgressData {
-                        progress: 1.0,
-                        status: "finished".into(),
-                    });
-                }
+        let provider = search_provider();
+        let filter = |keyword: &str| provider.filter_index(&search(keyword));
+        assert_eq!(ids(&filter("face black")), ["w1"]);
+        assert_eq!(ids(&filter(r#""black face""#)), ["w1"]);
+        assert!(filter(r#""face black""#).is_empty());
+    }
 
-                Ok::<_, anyhow::Error>(final_path.clone())
-            }
+    fn resolved_download(url: String, raw_url: String) -> ResolvedDownload {
+        ResolvedDownload {
+            item: index_item("w1", "Black Face", &[]),
+            entry: serde_json::from_value(serde_json::json!({
+                "version": "1.0.0",
+                "file_name": "face.bin",
+            }))
+            .unwrap(),
+            file_name: "face.bin".into(),
+            url,
+            raw_url,
         }
-        .await;
+    }
 
-        if download_result.is_err() {
-            let _ = fs::remove_file(&cleanup_path).await;
-        }
+    #[tokio::test]
+    async fn single_download_failure_keeps_original_error() {
+        let base = serve(Router::new()).await;
+        let url = format!("{base}/missing.bin");
+        let err = provider()
+            .open_download(&resolved_download(url.clone(), url))
+            .await
+            .unwrap_err();
+        assert!(err.downcast_ref::<MirrorFailures>().is_none());
+        assert!(matches!(classify_fetch_error(&err), FetchFailure::NotFound));
+    }
 
-        download_result
+    #[tokio::test]
+    async fn every_mirror_failure_is_collected() {
+        let base = serve(Router::new().route(
+            "/mirror.bin",
+            get(|| async { (StatusCode::BAD_GATEWAY, "down") }),
+        ))
+        .await;
+        let provider = provider();
+        // Raw 不改写非 GitHub 地址，raw_url 即为回退链上的第二个候选
+        provider.set_mirror_chain(vec![GitHubCdn::Raw]);
+        let err = provider
+            .open_download(&resolved_download(
+                format!("{base}/missing.bin"),
+                format!("{base}/mirror.bin"),
+            ))
+            .await
+            .unwrap_err();
+        let failures = err.downcast_ref::<MirrorFailures>().unwrap();
+        assert_eq!(failures.file_name, "face.bin");
+        let mirrors = failures
+            .failures
+            .iter()
+            .map(|failure| failure.mirror.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(mirrors, ["current", "Raw"]);
+        assert!(failures.failures[1].url.ends_with("/mirror.bin"));
+        assert!(matches!(
+            classify_fetch_error(&failures.failures[1].error),
+            FetchFailure::Network
+        ));
     }
-    async fn get_total_items(&self) -> anyhow::Result<u64> {
-        Ok(self.index.load().len() as u64)
+
+    fn is_cooldown(err: &anyhow::Error) -> bool {
+        matches!(
+            err.downcast_ref::<ProviderError>(),
+            Some(ProviderError::Cooldown(_))
+        )
     }
 
-    async fn probe_download_size(
-        &self,
-        item_id: String,
-        device: String,
-    ) -> anyhow::Result<Option<u64>> {
-        let entry = self.resolve_download_entry(item_id, device, false).await?;
-        let url = entry.url.clone().context("download url missing")?;
-        let client = crate::net::default_client();
-        let resp = client.get(&url).send().await?.error_for_status()?;
-        Ok(resp.content_length())
+    #[tokio::test]
+    async fn repeated_refresh_hits_cooldown_unless_forced() {
+        let base = serve(repo_router(INDEX_CSV, None)).await;
+        let provider = provider();
+        provider.refresh(&refresh_cfg(&base)).await.unwrap();
+
+        // 键顺序与空白不同，但解析后是同一份配置
+        let same = format!(
+            r#"{{ "raw_base_url": "{base}", "repo_ref": "main", "repo_name": "r", "repo_owner": "o" }}"#
+        );
+        let err = provider.refresh(&same).await.unwrap_err();
+        assert!(is_cooldown(&err), "{err:#}");
+        assert!(!provider.refresh_cooldown_remaining().is_zero());
+
+        provider.refresh_with(&same, true).await.unwrap();
+        assert!(matches!(provider.state(), ProviderState::Ready));
     }
-}
 
-fn strip_zero_width(input: &str) -> String {
-    input
-        .chars()
-        .filter(|c| !matches!(*c, '\u{200b}' | '\u{200c}' | '\u{200d}' | '\u{2060}' | '\u{feff}'))
-        .collect()
-}
+    // 上次部分失败时，冷却期内不会把结果报告为成功
+    #[tokio::test]
+    async fn cooldown_after_partial_refresh_is_not_ok() {
+        let base = serve(repo_router(INDEX_CSV, Some("devices_v2.json"))).await;
+        let provider = provider();
+        assert!(provider.refresh(&refresh_cfg(&base)).await.is_err());
+        assert!(matches!(provider.state(), ProviderState::Partial(_)));
+
+        let err = provider.refresh(&refresh_cfg(&base)).await.unwrap_err();
+        assert!(is_cooldown(&err), "{err:#}");
+    }
 
-fn sanitize_local_filename(input: &str) -> String {
-    let forbidden = ['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+    #[tokio::test]
+    async fn changed_config_skips_cooldown() {
+        let base = serve(repo_router(INDEX_CSV, None)).await;
+        let provider = provider();
+        provider.refresh(&refresh_cfg(&base)).await.unwrap();
 
-    let mut s: String = input
-        .chars()
-        .map(|c| if forbidden.contains(&c) { '_' } else { c })
-        .collect();
+        let mut cfg: serde_json::Value = serde_json::from_str(&refresh_cfg(&base)).unwrap();
+        cfg["verify_checksums"] = false.into();
+        cfg["preferred_language"] = "en".into();
+        provider.refresh(&cfg.to_string()).await.unwrap();
+    }
 
-    s = s.trim().to_string();
+    fn manifest_limits(max_bytes: usize, timeout: Duration) -> ManifestLimits {
+        ManifestLimits { max_bytes, timeout }
+    }
 
-    if s.is_empty() || s == "." || s == ".." {
-        s = "download".to_string();
+    #[tokio::test]
+    async fn oversized_manifest_is_rejected() {
+        let router = Router::new()
+            .route("/declared.json", get(|| async { "x".repeat(64) }))
+            .route(
+                "/streamed.json",
+                get(|| async {
+                    let chunks = futures_util::stream::iter(
+                        (0..8).map(|_| Ok::<_, std::io::Error>(vec![b'x'; 16])),
+                    );
+                    axum::body::Body::from_stream(chunks)
+                }),
+            );
+        let base = serve(router).await;
+        let client = reqwest::Client::new();
+        let limits = manifest_limits(32, Duration::from_secs(5));
+
+        // 声明了过大的 Content-Length，或未声明长度但读取中超限
+        for path in ["declared.json", "streamed.json"] {
+            let err = fetch_manifest_text(&client, &format!("{base}/{path}"), limits)
+                .await
+                .unwrap_err();
+            assert_eq!(
+                err.downcast_ref::<ProviderError>(),
+                Some(&ProviderError::ManifestTooLarge(32)),
+                "{path}"
+            );
+            assert!(matches!(classify_fetch_error(&err), FetchFailure::Fatal));
+        }
     }
 
-    s
+    #[tokio::test]
+    async fn slow_manifest_times_out() {
+        let router = Router::new().route(
+            "/slow.json",
+            get(|| async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                "{}"
+            }),
+        );
+        let base = serve(router).await;
+        let url = format!("{base}/slow.json");
+        let err = fetch_manifest_text(
+            &reqwest::Client::new(),
+            &url,
+            manifest_limits(1024, Duration::from_millis(100)),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<ProviderError>(),
+            Some(&ProviderError::Timeout(url))
+        );
+        assert!(matches!(classify_fetch_error(&err), FetchFailure::Network));
+    }
 }
@@ -0,0 +1,330 @@
+// 单仓库 provider：把任意 GitHub 仓库作为独立来源。
+// 仓库自带 index_v2.csv 时按多资源仓库处理，否则读取仓库根目录的 manifest 作为唯一资源。
+// 拉取、缓存与下载均复用 OfficialV2Provider 的实现
+use std::sync::Arc;
+
+use anyhow::{Context, anyhow};
+use async_trait::async_trait;
+
+use crate::{
+    cache::CacheStore,
+    cdn::GitHubCdn,
+    community::{
+        CommunityProvider,
+        models::{
+            common::{
+                ManifestItemV2, ManifestV2, PaidTypeV2, ProgressData, ProviderCapabilities,
+                ProviderState, SearchConfig,
+            },
+            official::IndexV2,
+        },
+        officialv2::{FetchFailure, OfficialV2Provider, classify_fetch_error},
+    },
+};
+
+const DEFAULT_REF: &str = "HEAD";
+
+pub struct SingleRepoProvider {
+    name: String,
+    owner: String,
+    repo: String,
+    reference: String,
+    inner: OfficialV2Provider,
+}
+
+impl SingleRepoProvider {
+    // source 可以是 `owner/name`，也可以是粘贴的 GitHub 地址（含 `/tree/<ref>` 时使用其中的 ref）；
+    // 显式传入的 reference 优先
+    pub fn new(
+        source: &str,
+        reference: Option<&str>,
+        cdn: GitHubCdn,
+        store: Arc<dyn CacheStore>,
+    ) -> anyhow::Result<Self> {
+        let (owner, repo, url_ref) = parse_repo_source(source)?;
+        let reference = reference
+            .map(str::trim)
+            .filter(|r| !r.is_empty())
+            .map(str::to_string)
+            .or(url_ref)
+            .unwrap_or_else(|| DEFAULT_REF.to_string());
+        Ok(Self {
+            // 不含 `:`，以免与聚合视图的限定 id 冲突
+            name: format!("SingleRepo@{owner}/{repo}"),
+            owner,
+            repo,
+            reference,
+            inner: OfficialV2Provider::new(cdn, store),
+        })
+    }
+
+    pub fn owner(&self) -> &str {
+        &self.owner
+    }
+
+    pub fn repo(&self) -> &str {
+        &self.repo
+    }
+
+    pub fn reference(&self) -> &str {
+        &self.reference
+    }
+
+    pub fn inner(&self) -> &OfficialV2Provider {
+        &self.inner
+    }
+
+    // 仓库没有索引时，以根目录 manifest 构造唯一的索引项。
+    // manifest 未声明 id（常见于 v1 仓库）时以 `owner_repo` 作为稳定 id，并写回缓存的 manifest。
+    // id 会作为缓存目录名，不能含 `/`
+    async fn refresh_single_item(&self) -> anyhow::Result<()> {
+        let mut manifest = self
+            .inner
+            .get_manifest_with(&self.owner, &self.repo, &self.reference, true)
+            .await
+            .with_context(|| format!("failed to fetch manifest of {}/{}", self.owner, self.repo))?;
+        if manifest.item.id.trim().is_empty() {
+            manifest.item.id = format!("{}_{}", self.owner, self.repo);
+            self.inner
+                .store_manifest(&self.owner, &self.repo, &self.reference, &manifest)
+                .await;
+        }
+        self.inner.load_single_item(self.index_item_from(&manifest));
+        Ok(())
+    }
+
+    fn index_item_from(&self, manifest: &ManifestV2) -> IndexV2 {
+        let item = &manifest.item;
+        let mut devices = manifest
+            .downloads
+            .keys()
+            .filter(|key| key.as_str() != "default")
+            .cloned()
+            .collect::<Vec<_>>();
+        devices.sort();
+        IndexV2 {
+            id: item.id.clone(),
+            name: item.name.clone(),
+            restype: item.restype.clone(),
+            repo_owner: self.owner.clone(),
+            repo_name: self.repo.clone(),
+            repo_commit_hash: self.reference.clone(),
+            icon: item.icon.clone(),
+            cover: item.cover.clone(),
+            tags: item.tags.clone(),
+            device_vendors: Vec::new(),
+            devices,
+            paid_type: item.paid_type.clone().unwrap_or(PaidTypeV2::Free),
+        }
+    }
+}
+
+// 解析 `owner/name`、`github.com/owner/name`、`https://github.com/owner/name(.git)(/tree/<ref>)`
+pub fn parse_repo_source(source: &str) -> anyhow::Result<(String, String, Option<String>)> {
+    let trimmed = source.trim().trim_end_matches('/');
+    let path = ["https://", "http://"]
+        .iter()
+        .find_map(|scheme| trimmed.strip_prefix(scheme))
+        .unwrap_or(trimmed);
+    let path = ["www.github.com/", "github.com/"]
+        .iter()
+        .find_map(|host| path.strip_prefix(host))
+        .unwrap_or(path);
+
+    let parts = path.split('/').collect::<Vec<_>>();
+    let (owner, repo) = match parts.as_slice() {
+        [owner, repo, ..] => (*owner, repo.trim_end_matches(".git")),
+        _ => return Err(anyhow!("`{source}` is not a GitHub repository")),
+    };
+    let valid = |part: &str| {
+        !part.is_empty()
+            && part != "."
+            && part != ".."
+            && part
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    };
+    if !valid(owner) || !valid(repo) {
+        return Err(anyhow!("`{source}` is not a GitHub repository"));
+    }
+
+    let reference = match parts.get(2..) {
+        Some(["tree", rest @ ..]) if !rest.is_empty() => Some(rest.join("/")),
+        Some([]) | None => None,
+        Some(_) => return Err(anyhow!("unsupported GitHub url `{source}`")),
+    };
+    Ok((owner.to_string(), repo.to_string(), reference))
+}
+
+#[async_trait]
+impl CommunityProvider for SingleRepoProvider {
+    fn provider_name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn into_any_arc(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
+        self
+    }
+
+    // cfg 与 OfficialV2Provider 相同（cdn 等），仓库字段固定为本仓库
+    async fn refresh(&self, cfg: &str) -> anyhow::Result<()> {
+        let mut cfg = if cfg.trim().is_empty() {
+            serde_json::Map::new()
+        } else {
+            serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(cfg)
+                .with_context(|| format!("invalid refresh config: {cfg}"))?
+        };
+        cfg.insert("repo_owner".into(), self.owner.clone().into());
+        cfg.insert("repo_name".into(), self.repo.clone().into());
+        cfg.insert("repo_ref".into(), self.reference.clone().into());
+        let cfg = serde_json::Value::Object(cfg).to_string();
+
+        match self.inner.refresh(&cfg).await {
+            Ok(()) => Ok(()),
+            // 仓库没有 index_v2.csv：按单资源仓库处理
+            Err(err) if matches!(classify_fetch_error(&err), FetchFailure::NotFound) => {
+                self.refresh_single_item().await
+            }
+            Err(err) => Err(err),
+        }
+    }
+
+    fn state(&self) -> ProviderState {
+        self.inner.state()
+    }
+
+    fn capabilities(&self) -> ProviderCapabilities {
+        self.inner.capabilities()
+    }
+
+    fn subscribe_state(&self) -> tokio::sync::watch::Receiver<ProviderState> {
+        self.inner.subscribe_state()
+    }
+
+    async fn get_page(
+        &self,
+        page: u32,
+        limit: u32,
+        search: SearchConfig,
+    ) -> anyhow::Result<Vec<ManifestItemV2>> {
+        self.inner.get_page(page, limit, search).await
+    }
+
+    async fn get_categories(&self) -> anyhow::Result<Vec<String>> {
+        self.inner.get_categories().await
+    }
+
+    async fn get_item_manifest(&self, item_id: String) -> anyhow::Result<ManifestV2> {
+        self.inner.get_item_manifest(item_id).await
+    }
+
+    async fn download(
+        &self,
+        item_id: String,
+        device: String,
+        progress_cb: Option<Box<dyn Fn(ProgressData) + Send>>,
+    ) -> anyhow::Result<std::path::PathBuf> {
+        self.inner.download(item_id, device, progress_cb).await
+    }
+
+    async fn get_total_items(&self) -> anyhow::Result<u64> {
+        self.inner.get_total_items().await
+    }
+
+    async fn get_items_by_ids(
+        &self,
+        ids: &[String],
+    ) -> anyhow::Result<Vec<Option<ManifestItemV2>>> {
+        self.inner.get_items_by_ids(ids).await
+    }
+
+    async fn suggest(&self, prefix: &str, limit: usize) -> anyhow::Result<Vec<String>> {
+        self.inner.suggest(prefix, limit).await
+    }
+
+    async fn probe_download_size(
+        &self,
+        item_id: String,
+        device: String,
+    ) -> anyhow::Result<Option<u64>> {
+        self.inner.probe_download_size(item_id, device).await
+    }
+
+    // 仓库字段在 refresh 时总会被本仓库覆盖，其余设置（cdn 等）交给内部 provider
+    fn export_config(&self) -> serde_json::Value {
+        self.inner.export_config()
+    }
+
+    fn apply_config(&self, cfg: serde_json::Value) -> anyhow::Result<()> {
+        self.inner.apply_config(cfg)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, routing::get};
+
+    use super::*;
+    use crate::{cache::FsCacheStore, test_support::serve};
+
+    // 没有 index_v2.csv、manifest 也未声明 id 的旧仓库
+    #[tokio::test]
+    async fn single_item_without_id_gets_owner_repo_id() {
+        let router = Router::new()
+            .route(
+                "/someone/face/HEAD/manifest.json",
+                get(|| async {
+                    r#"{"item":{"name":"Face","restype":"watchface","author":[]},
+                    "downloads":{"o66":{"version":"1.0","file_name":"face.bin"}}}"#
+                }),
+            )
+            .route("/someone/face/HEAD/face.bin", get(|| async { "face" }));
+        let base = serve(router).await;
+        let dir = tempfile::tempdir().unwrap();
+        let provider = SingleRepoProvider::new(
+            "someone/face",
+            None,
+            GitHubCdn::Raw,
+            Arc::new(FsCacheStore::new(dir.path())),
+        )
+        .unwrap();
+        let cfg = serde_json::json!({ "raw_base_url": base }).to_string();
+        provider.refresh(&cfg).await.unwrap();
+
+        assert!(provider.inner().has_item("someone_face"));
+        let manifest = provider
+            .get_item_manifest("someone_face".into())
+            .await
+            .unwrap();
+        assert_eq!(manifest.item.id, "someone_face");
+        // 再次刷新得到同一个 id
+        provider
+            .inner()
+            .set_refresh_cooldown(std::time::Duration::ZERO);
+        provider.refresh(&cfg).await.unwrap();
+        assert!(provider.inner().has_item("someone_face"));
+
+        // id 可直接作为缓存目录：下载产物能被列出和清理
+        let path = provider
+            .download("someone_face".into(), "o66".into(), None)
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "face");
+        let cached = provider.inner().list_cached().await.unwrap();
+        assert_eq!(cached.len(), 1);
+        assert_eq!(cached[0].item_id, "someone_face");
+        assert_eq!(cached[0].files, [("face.bin".to_string(), 4)]);
+
+        provider
+            .inner()
+            .clear_item_cache("someone_face")
+            .await
+            .unwrap();
+        assert!(provider.inner().list_cached().await.unwrap().is_empty());
+        assert!(!path.exists());
+    }
+}
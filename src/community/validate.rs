@@ -0,0 +1,117 @@
+use crate::community::{
+    error::{ManifestValidationError, ManifestViolation},
+    models::{common::ManifestV2, official::IndexV2},
+};
+
+// 校验 manifest 内容；传入索引条目时同时检查与索引的一致性
+pub fn validate_manifest(
+    manifest: &ManifestV2,
+    index: Option<&IndexV2>,
+) -> Result<(), ManifestValidationError> {
+    let mut violations = Vec::new();
+    let mut violate = |field: String, message: String| {
+        violations.push(ManifestViolation { field, message });
+    };
+
+    if manifest.item.id.trim().is_empty() {
+        violate("item.id".into(), "must not be empty".into());
+    }
+    if manifest.item.name.trim().is_empty() {
+        violate("item.name".into(), "must not be empty".into());
+    }
+    if let Some(index) = index {
+        if manifest.item.id != index.id {
+            violate(
+                "item.id".into(),
+                format!(
+                    "`{}` does not match index id `{}`",
+                    manifest.item.id, index.id
+                ),
+            );
+        }
+        if manifest.item.restype != index.restype {
+            violate(
+                "item.restype".into(),
+                format!(
+                    "{:?} does not match index restype {:?}",
+                    manifest.item.restype, index.restype
+                ),
+            );
+        }
+    }
+
+    if manifest.downloads.is_empty() {
+        violate("downloads".into(), "must contain at least one entry".into());
+    }
+    let mut devices = manifest.downloads.keys().collect::<Vec<_>>();
+    devices.sort();
+    for device in devices {
+        let entry = &manifest.downloads[device];
+        if entry.version.trim().is_empty() {
+            violate(
+                format!("downloads.{device}.version"),
+                "must not be empty".into(),
+            );
+        }
+        let has_url = entry
+            .url
+            .as_deref()
+            .is_some_and(|url| !url.trim().is_empty());
+        if entry.file_name.trim().is_empty() && !has_url {
+            violate(
+                format!("downloads.{device}.file_name"),
+                "either file_name or url is required".into(),
+            );
+        }
+    }
+
+    let assets = [
+        ("item.icon".to_string(), &manifest.item.icon),
+        ("item.cover".to_string(), &manifest.item.cover),
+    ]
+    .into_iter()
+    .chain(
+        manifest
+            .item
+            .preview
+            .iter()
+            .enumerate()
+            .map(|(i, path)| (format!("item.preview[{i}]"), path)),
+    );
+    for (field, path) in assets {
+        // cover 可缺省（列表回退到索引中的封面），icon 与预览图必须给出
+        if path.trim().is_empty() {
+            if field != "item.cover" {
+                violate(field, "must not be empty".into());
+            }
+        } else if is_local_fs_path(path) {
+            violate(
+                field,
+                format!("`{path}` looks like a local filesystem path, use a repo-relative path"),
+            );
+        }
+    }
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(ManifestValidationError {
+            item_id: manifest.item.id.clone(),
+            violations,
+        })
+    }
+}
+
+// 以 `/` 开头视为仓库根目录相对路径，这里只拦截明显的本机路径
+fn is_local_fs_path(path: &str) -> bool {
+    let path = path.trim();
+    let bytes = path.as_bytes();
+    let windows_drive = bytes.len() >= 3
+        && bytes[0].is_ascii_alphabetic()
+        && bytes[1] == b':'
+        && (bytes[2] == b'\\' || bytes[2] == b'/');
+    windows_drive
+        || path.starts_with("\\\\")
+        || path.starts_with("~/")
+        || path.to_ascii_lowercase().starts_with("file:")
+}
@@ -0,0 +1,693 @@
+// 各 provider 共用的产物下载流程：临时文件落盘与续传、进度回调、sha256 校验与 HTML 错误页拦截
+use std::{
+    cmp, fmt,
+    path::{Path, PathBuf},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, anyhow};
+use futures_util::StreamExt;
+use reqwest::{Client, StatusCode};
+use sha2::{Digest, Sha256};
+use tokio::{
+    fs::{self, File, OpenOptions},
+    io::{AsyncReadExt, AsyncWrite, AsyncWriteExt, BufWriter},
+};
+
+use crate::community::models::common::ProgressData;
+
+pub type ProgressCb = Box<dyn Fn(ProgressData) + Send>;
+
+// download_to_file / save_response 的选项，缺省即普通的一次性下载
+#[derive(Debug, Clone, Default)]
+pub struct DownloadOptions {
+    pub file_name: Option<String>, // 用于格式嗅探与错误信息，缺省取 dest 的文件名
+    pub expected_sha256: Option<String>, // 边写边算摘要，完成后校验，不一致时丢弃文件
+    pub compute_sha256: bool,      // 未提供 expected_sha256 时也计算摘要，结果见 DownloadedFile
+    // 使用固定的 `<dest>.part` 临时文件：已有部分内容时带 Range 请求续传，失败时保留以便下次续传
+    pub resume: bool,
+    pub buffer_size: Option<usize>, // 写缓冲大小，缺省使用 BufWriter 默认值
+}
+
+#[derive(Debug, Clone)]
+pub struct DownloadedFile {
+    pub path: PathBuf,
+    pub size: u64,              // 文件总大小（含续传前已有部分）
+    pub sha256: Option<String>, // 计算了摘要时为小写十六进制
+    pub resumed_from: u64,      // 续传起点，0 表示完整下载
+}
+
+// 下载 url 到 dest：写入同目录临时文件，完成后原子重命名；失败时清理临时文件（续传模式除外）
+pub async fn download_to_file(
+    client: &Client,
+    url: &str,
+    dest: &Path,
+    opts: DownloadOptions,
+    progress: Option<ProgressCb>,
+) -> anyhow::Result<DownloadedFile> {
+    if let Some(cb) = progress.as_ref() {
+        cb(ProgressData {
+            progress: 0.0,
+            status: "".into(),
+        });
+    }
+
+    let mut request = client.get(url);
+    if opts.resume {
+        let existing = fs::metadata(resume_path(dest))
+            .await
+            .map(|meta| meta.len())
+            .unwrap_or(0);
+        if existing > 0 {
+            request = request.header(reqwest::header::RANGE, format!("bytes={existing}-"));
+        }
+    }
+    let response = request
+        .send()
+        .await
+        .with_context(|| format!("failed to request {url}"))?
+        .error_for_status()
+        .with_context(|| format!("download request returned error for {url}"))?;
+
+    save_response(response, dest, opts, progress).await
+}
+
+// 把已建立的下载响应保存到 dest，供需要自行建立连接（镜像回退、自定义重定向）的调用方使用。
+// 响应为 206 时按 Content-Range 追加到续传临时文件之后
+pub async fn save_response(
+    response: reqwest::Response,
+    dest: &Path,
+    opts: DownloadOptions,
+    mut progress: Option<ProgressCb>,
+) -> anyhow::Result<DownloadedFile> {
+    let file_name = opts
+        .file_name
+        .clone()
+        .or_else(|| {
+            dest.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| "download".to_string());
+    let dir = dest
+        .parent()
+        .map(PathBuf::from)
+        .ok_or_else(|| anyhow!("invalid download path {}", dest.display()))?;
+    fs::create_dir_all(&dir)
+        .await
+        .with_context(|| format!("failed to create directory {}", dir.display()))?;
+
+    let tmp_path = if opts.resume {
+        resume_path(dest)
+    } else {
+        let unique_suffix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        dir.join(format!(
+            "{}.{}.part",
+            unique_suffix,
+            sanitize_local_filename(&file_name)
+        ))
+    };
+
+    // 续传模式保留已下载部分以便下次续传，其余情况出错或被取消时删除
+    let mut guard = TempFileGuard {
+        path: tmp_path.clone(),
+        remove: !opts.resume,
+    };
+    let expected_sha256 = normalize_sha256(opts.expected_sha256.as_deref());
+    let result = async {
+        let offset = resume_offset(&response, &tmp_path).await?;
+        let mut hasher = (expected_sha256.is_some() || opts.compute_sha256).then(Sha256::new);
+        let file = if offset > 0 {
+            if let Some(hasher) = hasher.as_mut() {
+                hash_existing(&tmp_path, hasher).await?;
+            }
+            OpenOptions::new().append(true).open(&tmp_path).await
+        } else {
+            File::create(&tmp_path).await
+        }
+        .with_context(|| format!("failed to open temp file {}", tmp_path.display()))?;
+        let mut writer = match opts.buffer_size {
+            Some(capacity) => BufWriter::with_capacity(capacity.max(1), file),
+            None => BufWriter::new(file),
+        };
+
+        // 续传时首块位于文件中间，不做格式嗅探
+        let size = copy_body(
+            response,
+            &mut writer,
+            &file_name,
+            offset == 0,
+            offset,
+            &mut hasher,
+            &mut progress,
+        )
+        .await?;
+        drop(writer);
+
+        let sha256 = hasher.map(|hasher| format!("{:x}", hasher.finalize()));
+        verify_sha256(&file_name, expected_sha256.as_deref(), sha256.as_deref())?;
+
+        fs::rename(&tmp_path, dest).await.with_context(|| {
+            format!(
+                "failed to move downloaded file {} -> {}",
+                tmp_path.display(),
+                dest.display()
+            )
+        })?;
+        Ok::<_, anyhow::Error>(DownloadedFile {
+            path: dest.to_path_buf(),
+            size,
+            sha256,
+            resumed_from: offset,
+        })
+    }
+    .await;
+
+    match result {
+        Ok(downloaded) => {
+            guard.remove = false;
+            if let Some(cb) = progress.as_ref() {
+                cb(ProgressData {
+                    progress: 1.0,
+                    status: "finished".into(),
+                });
+            }
+            Ok(downloaded)
+        }
+        Err(err) => {
+            // 校验失败说明内容已损坏，续传模式也一并丢弃
+            if err.downcast_ref::<Sha256Mismatch>().is_some() {
+                guard.remove = true;
+            }
+            Err(err)
+        }
+    }
+}
+
+// 下载任务被取消（future 被丢弃）时异步清理不会执行，由 Drop 同步删除临时文件
+struct TempFileGuard {
+    path: PathBuf,
+    remove: bool,
+}
+
+impl Drop for TempFileGuard {
+    fn drop(&mut self) {
+        if self.remove {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+// 把已建立的下载响应写入任意 writer，返回写入字节数。
+// expected_sha256 非空时边写边算摘要，结束后校验。
+// 出错时 writer 中可能残留部分数据，由调用方清理
+pub async fn stream_response<W>(
+    response: reqwest::Response,
+    writer: &mut W,
+    file_name: &str,
+    expected_sha256: Option<&str>,
+    progress_cb: &mut Option<ProgressCb>,
+) -> anyhow::Result<u64>
+where
+    W: AsyncWrite + Unpin + Send,
+{
+    let expected_sha256 = normalize_sha256(expected_sha256);
+    let mut hasher = expected_sha256.as_ref().map(|_| Sha256::new());
+    let downloaded = copy_body(
+        response,
+        writer,
+        file_name,
+        true,
+        0,
+        &mut hasher,
+        progress_cb,
+    )
+    .await?;
+    let actual = hasher.map(|hasher| format!("{:x}", hasher.finalize()));
+    verify_sha256(file_name, expected_sha256.as_deref(), actual.as_deref())?;
+    Ok(downloaded)
+}
+
+// 下载循环：首块格式嗅探、边写边算摘要，进度按 200ms 或 1% 节流回调。
+// start 为续传起点，计入进度与返回的总字节数
+async fn copy_body<W>(
+    response: reqwest::Response,
+    writer: &mut W,
+    file_name: &str,
+    check_head: bool,
+    start: u64,
+    hasher: &mut Option<Sha256>,
+    progress_cb: &mut Option<ProgressCb>,
+) -> anyhow::Result<u64>
+where
+    W: AsyncWrite + Unpin + Send,
+{
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|s| s.to_string());
+    let check_mode = artifact_check_mode(file_name);
+
+    let total = response.content_length().map(|len| len + start);
+    let mut stream = response.bytes_stream();
+    let mut downloaded: u64 = start;
+    let mut first_chunk = true;
+    let mut last_emit = Instant::now();
+    let step_bytes = total.map(|t| cmp::max(1, t / 100));
+    let mut last_reported = start;
+
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk.with_context(|| "failed to read download chunk")?;
+        if first_chunk {
+            if check_head {
+                check_artifact_head(file_name, content_type.as_deref(), &chunk, check_mode)?;
+            }
+            first_chunk = false;
+        }
+        downloaded += chunk.len() as u64;
+        if let Some(hasher) = hasher.as_mut() {
+            hasher.update(&chunk);
+        }
+        writer
+            .write_all(chunk.as_ref())
+            .await
+            .with_context(|| "failed to write download chunk")?;
+
+        if let Some(cb) = progress_cb.as_ref() {
+            let mut emit = last_emit.elapsed() >= Duration::from_millis(200);
+            if !emit
+                && let Some(step) = step_bytes
+                && (downloaded >= last_reported.saturating_add(step)
+                    || total.map(|t| downloaded >= t).unwrap_or(false))
+            {
+                emit = true;
+            }
+
+            if emit {
+                let progress = match total {
+                    Some(total_len) if total_len > 0 => {
+                        (downloaded as f32 / total_len as f32).clamp(0.0, 1.0)
+                    }
+                    _ => 0.0,
+                };
+                cb(ProgressData {
+                    progress,
+                    status: "".into(),
+                });
+                last_emit = Instant::now();
+                if step_bytes.is_some() {
+                    last_reported = downloaded;
+                }
+            }
+        }
+    }
+
+    writer
+        .flush()
+        .await
+        .with_context(|| "failed to flush download writer")?;
+
+    Ok(downloaded)
+}
+
+fn resume_path(dest: &Path) -> PathBuf {
+    let mut path = dest.as_os_str().to_owned();
+    path.push(".part");
+    PathBuf::from(path)
+}
+
+// 206 响应的续传起点；必须与临时文件已有长度一致，否则已有内容无法拼接
+async fn resume_offset(response: &reqwest::Response, tmp_path: &Path) -> anyhow::Result<u64> {
+    if response.status() != StatusCode::PARTIAL_CONTENT {
+        return Ok(0);
+    }
+    let start = response
+        .headers()
+        .get(reqwest::header::CONTENT_RANGE)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().strip_prefix("bytes "))
+        .and_then(|v| v.split('-').next())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .ok_or_else(|| anyhow!("partial response without a valid content-range"))?;
+    let existing = fs::metadata(tmp_path)
+        .await
+        .map(|meta| meta.len())
+        .unwrap_or(0);
+    if start != existing {
+        let _ = fs::remove_file(tmp_path).await;
+        return Err(anyhow!(
+            "cannot resume download: server range starts at {start}, local file has {existing} bytes"
+        ));
+    }
+    Ok(start)
+}
+
+async fn hash_existing(path: &Path, hasher: &mut Sha256) -> anyhow::Result<()> {
+    let mut file = File::open(path)
+        .await
+        .with_context(|| format!("failed to open {}", path.display()))?;
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file
+            .read(&mut buf)
+            .await
+            .with_context(|| format!("failed to read {}", path.display()))?;
+        if n == 0 {
+            return Ok(());
+        }
+        hasher.update(&buf[..n]);
+    }
+}
+
+fn normalize_sha256(value: Option<&str>) -> Option<String> {
+    value
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(str::to_ascii_lowercase)
+}
+
+// 摘要不一致，下载内容已损坏
+#[derive(Debug)]
+struct Sha256Mismatch {
+    file_name: String,
+    expected: String,
+    actual: String,
+}
+
+impl fmt::Display for Sha256Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "sha256 mismatch for {}: expected {}, got {}",
+            self.file_name, self.expected, self.actual
+        )
+    }
+}
+
+impl std::error::Error for Sha256Mismatch {}
+
+fn verify_sha256(
+    file_name: &str,
+    expected: Option<&str>,
+    actual: Option<&str>,
+) -> anyhow::Result<()> {
+    match (expected, actual) {
+        (Some(expected), Some(actual)) if expected != actual => Err(Sha256Mismatch {
+            file_name: file_name.to_string(),
+            expected: expected.to_string(),
+            actual: actual.to_string(),
+        }
+        .into()),
+        _ => Ok(()),
+    }
+}
+
+// 产物校验模式：能可靠嗅探格式的类型严格报错，其余仅告警
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ArtifactCheckMode {
+    Strict,
+    WarnOnly,
+}
+
+const ZIP_MAGIC: &[u8] = b"PK\x03\x04";
+
+fn artifact_extension(file_name: &str) -> String {
+    file_name
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase())
+        .unwrap_or_default()
+}
+
+fn artifact_check_mode(file_name: &str) -> ArtifactCheckMode {
+    match artifact_extension(file_name).as_str() {
+        // 快应用 rpk 与 zip 均为 zip 容器，可按魔数确认
+        "rpk" | "zip" => ArtifactCheckMode::Strict,
+        _ => ArtifactCheckMode::WarnOnly,
+    }
+}
+
+fn looks_like_html(head: &[u8]) -> bool {
+    let head = head.strip_prefix(b"\xef\xbb\xbf").unwrap_or(head);
+    let start = head
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(head.len());
+    let sniff = &head[start..cmp::min(head.len(), start + 16)];
+    let sniff = String::from_utf8_lossy(sniff).to_ascii_lowercase();
+    sniff.starts_with("<!doctype html") || sniff.starts_with("<html")
+}
+
+// 镜像配置错误时常以 200 返回 HTML 错误页，这里在写入首块前拦截。
+// 内容本身是 HTML 一律报错；仅响应头可疑时，无法可靠嗅探的类型只告警
+fn check_artifact_head(
+    file_name: &str,
+    content_type: Option<&str>,
+    head: &[u8],
+    mode: ArtifactCheckMode,
+) -> anyhow::Result<()> {
+    if looks_like_html(head) {
+        return Err(anyhow!(
+            "下载内容异常，请尝试切换下载源: server returned an HTML page instead of `{}`",
+            file_name
+        ));
+    }
+
+    let html_content_type = content_type
+        .map(|c| c.trim().to_ascii_lowercase().starts_with("text/html"))
+        .unwrap_or(false);
+    let problem = if html_content_type {
+        Some(format!(
+            "unexpected content-type `{}` for `{}`",
+            content_type.unwrap_or_default(),
+            file_name
+        ))
+    } else if mode == ArtifactCheckMode::Strict && !head.starts_with(ZIP_MAGIC) {
+        Some(format!("`{}` is not a valid zip archive", file_name))
+    } else {
+        None
+    };
+
+    match (problem, mode) {
+        (None, _) => Ok(()),
+        (Some(reason), ArtifactCheckMode::Strict) => {
+            Err(anyhow!("下载内容异常，请尝试切换下载源: {}", reason))
+        }
+        (Some(reason), ArtifactCheckMode::WarnOnly) => {
+            log::warn!("[download] suspicious download artifact: {reason}");
+            Ok(())
+        }
+    }
+}
+
+// 把远端文件名转换为可安全落盘的本地文件名
+pub fn sanitize_local_filename(input: &str) -> String {
+    let forbidden = ['/', '\\', ':', '*', '?', '"', '<', '>', '|'];
+
+    let mut s: String = input
+        .chars()
+        .map(|c| if forbidden.contains(&c) { '_' } else { c })
+        .collect();
+
+    s = s.trim().to_string();
+
+    if s.is_empty() || s == "." || s == ".." {
+        s = "download".to_string();
+    }
+
+    s
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use axum::{
+        Router,
+        body::{Body, Bytes},
+        http::{HeaderMap, header},
+        response::IntoResponse,
+        routing::get,
+    };
+
+    use super::*;
+    use crate::test_support::serve;
+
+    const HELLO_SHA256: &str = "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9";
+
+    fn leftover_parts(dir: &Path) -> Vec<PathBuf> {
+        std::fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "part"))
+            .collect()
+    }
+
+    // 带 Range 时只返回请求的后半段
+    async fn hello(headers: HeaderMap) -> impl IntoResponse {
+        const BODY: &str = "hello world";
+        let start = headers
+            .get(header::RANGE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("bytes="))
+            .and_then(|v| v.trim_end_matches('-').parse::<usize>().ok());
+        match start {
+            Some(start) => (
+                StatusCode::PARTIAL_CONTENT,
+                [(
+                    header::CONTENT_RANGE,
+                    format!("bytes {start}-{}/{}", BODY.len() - 1, BODY.len()),
+                )],
+                &BODY[start..],
+            )
+                .into_response(),
+            None => BODY.into_response(),
+        }
+    }
+
+    fn router() -> Router {
+        Router::new()
+            .route("/hello.bin", get(hello))
+            .route(
+                "/error.bin",
+                get(|| async { "<!DOCTYPE html><html>rate limited</html>" }),
+            )
+            .route(
+                "/large.bin",
+                get(|| async {
+                    let chunks = futures_util::stream::iter(
+                        (0..1000).map(|_| Ok::<_, std::io::Error>(Bytes::from(vec![0u8; 1000]))),
+                    );
+                    (
+                        [(header::CONTENT_LENGTH, "1000000")],
+                        Body::from_stream(chunks),
+                    )
+                }),
+            )
+    }
+
+    #[tokio::test]
+    async fn download_renames_temp_file_into_place() {
+        let base = serve(router()).await;
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("hello.bin");
+        let downloaded = download_to_file(
+            &Client::new(),
+            &format!("{base}/hello.bin"),
+            &dest,
+            DownloadOptions {
+                expected_sha256: Some(HELLO_SHA256.to_uppercase()),
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(downloaded.path, dest);
+        assert_eq!(downloaded.size, 11);
+        assert_eq!(downloaded.sha256.as_deref(), Some(HELLO_SHA256));
+        assert_eq!(std::fs::read(&dest).unwrap(), b"hello world");
+        assert!(leftover_parts(dir.path()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn failed_download_removes_temp_file() {
+        let base = serve(router()).await;
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("error.bin");
+        let err = download_to_file(
+            &Client::new(),
+            &format!("{base}/error.bin"),
+            &dest,
+            DownloadOptions::default(),
+            None,
+        )
+        .await
+        .unwrap_err();
+
+        assert!(format!("{err:#}").contains("HTML page"));
+        assert!(!dest.exists());
+        assert!(leftover_parts(dir.path()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn partial_response_resumes_existing_part_file() {
+        let base = serve(router()).await;
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("hello.bin");
+        std::fs::write(resume_path(&dest), b"hello ").unwrap();
+
+        let downloaded = download_to_file(
+            &Client::new(),
+            &format!("{base}/hello.bin"),
+            &dest,
+            DownloadOptions {
+                expected_sha256: Some(HELLO_SHA256.into()),
+                resume: true,
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(downloaded.resumed_from, 6);
+        assert_eq!(downloaded.size, 11);
+        assert_eq!(std::fs::read(&dest).unwrap(), b"hello world");
+        assert!(!resume_path(&dest).exists());
+    }
+
+    // 校验失败时连续传的临时文件也一并丢弃
+    #[tokio::test]
+    async fn sha256_mismatch_discards_download() {
+        let base = serve(router()).await;
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("hello.bin");
+        let err = download_to_file(
+            &Client::new(),
+            &format!("{base}/hello.bin"),
+            &dest,
+            DownloadOptions {
+                expected_sha256: Some("00".repeat(32)),
+                resume: true,
+                ..Default::default()
+            },
+            None,
+        )
+        .await
+        .unwrap_err();
+
+        assert!(err.downcast_ref::<Sha256Mismatch>().is_some());
+        assert!(!dest.exists());
+        assert!(leftover_parts(dir.path()).is_empty());
+    }
+
+    // 1000 个数据块按 1% 步长节流，回调次数远少于块数，进度单调并以 1.0 结束
+    #[tokio::test]
+    async fn progress_callbacks_are_throttled() {
+        let base = serve(router()).await;
+        let dir = tempfile::tempdir().unwrap();
+        let reports = Arc::new(Mutex::new(Vec::new()));
+        let sink = reports.clone();
+        download_to_file(
+            &Client::new(),
+            &format!("{base}/large.bin"),
+            &dir.path().join("large.bin"),
+            DownloadOptions::default(),
+            Some(Box::new(move |data: ProgressData| {
+                sink.lock().unwrap().push(data.progress)
+            })),
+        )
+        .await
+        .unwrap();
+
+        let reports = reports.lock().unwrap();
+        assert!(reports.len() <= 110, "{} progress reports", reports.len());
+        assert!(reports.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(reports.first(), Some(&0.0));
+        assert_eq!(reports.last(), Some(&1.0));
+    }
+}
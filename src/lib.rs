@@ -1,3 +1,9 @@
+pub mod cache;
 pub mod cdn;
 pub mod community;
+pub mod download;
 pub mod net;
+pub mod version;
+
+#[cfg(test)]
+mod test_support;
@@ -1,9 +1,61 @@
-use reqwest::{Client, ClientBuilder};
+use std::{
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+};
+
+use reqwest::{
+    Client, ClientBuilder,
+    header::{HeaderMap, HeaderName, HeaderValue},
+};
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_USER_AGENT: &str =
+    concat!("AstroBox-NG-Module-Provider/", env!("CARGO_PKG_VERSION"));
+
+// 全局请求配置：部分镜像会限流默认 UA，私有镜像可能需要额外的鉴权头
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct ClientConfig {
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
+}
+
+static CLIENT_CONFIG: OnceLock<Mutex<ClientConfig>> = OnceLock::new();
+
+pub fn set_client_config(cfg: ClientConfig) {
+    let config = CLIENT_CONFIG.get_or_init(|| Mutex::new(ClientConfig::default()));
+    *config.lock().unwrap() = cfg;
+}
+
+pub fn client_config() -> ClientConfig {
+    let config = CLIENT_CONFIG.get_or_init(|| Mutex::new(ClientConfig::default()));
+    config.lock().unwrap().clone()
+}
 
 pub fn default_client() -> Client {
-    netcfg::default_client()
+    default_client_builder()
+        .build()
+        .unwrap_or_else(|_| netcfg::default_client())
 }
 
 pub fn default_client_builder() -> ClientBuilder {
+    let cfg = client_config();
+
+    let mut headers = HeaderMap::new();
+    for (name, value) in &cfg.headers {
+        match (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            (Ok(name), Ok(value)) => {
+                headers.insert(name, value);
+            }
+            _ => log::warn!("[net] ignored invalid default header `{name}`"),
+        }
+    }
+
     netcfg::default_client_builder()
+        .user_agent(cfg.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT))
+        .default_headers(headers)
 }
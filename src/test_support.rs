@@ -0,0 +1,174 @@
+// 单元测试共用的本地 HTTP 服务与桩 provider
+use std::{
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use anyhow::anyhow;
+use async_trait::async_trait;
+use axum::Router;
+
+use crate::community::{
+    CommunityProvider,
+    models::{
+        common::{
+            ManifestItemV2, ManifestV2, PaidTypeV2, ProgressData, ProviderState, ResourceTypeV2,
+            SearchConfig,
+        },
+        official::IndexV2,
+    },
+};
+
+// 在随机端口上启动服务，返回 `http://127.0.0.1:<port>`；服务随测试运行时一同结束
+pub(crate) async fn serve(router: Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .expect("bind test server");
+    let addr = listener.local_addr().expect("test server address");
+    tokio::spawn(async move {
+        axum::serve(listener, router).await.ok();
+    });
+    format!("http://{addr}")
+}
+
+// 官方索引中的一条表盘记录，repo_name 与 id 相同
+pub(crate) fn index_item(id: &str, name: &str, tags: &[&str]) -> IndexV2 {
+    IndexV2 {
+        id: id.into(),
+        name: name.into(),
+        restype: ResourceTypeV2::WatchFace,
+        repo_owner: "o".into(),
+        repo_name: id.into(),
+        repo_commit_hash: "c".into(),
+        icon: "icon.png".into(),
+        cover: "cover.png".into(),
+        tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        device_vendors: Vec::new(),
+        devices: Vec::new(),
+        paid_type: PaidTypeV2::Free,
+    }
+}
+
+// StubProvider::download 的行为
+pub(crate) enum StubDownload {
+    Sleep(Duration), // 等待后返回以 item_id 命名的路径
+    Fail,
+    File { url: String, dir: PathBuf }, // 经共享下载流程把 url 存为 dir/<item_id>.bin
+}
+
+// 桩 provider：failing 时所有请求都失败，否则列表为空、download 按 StubDownload 执行；
+// 记录同时进行中的下载数及其峰值
+pub(crate) struct StubProvider {
+    name: String,
+    failing: bool,
+    download: StubDownload,
+    running: AtomicUsize,
+    pub peak: AtomicUsize,
+}
+
+impl StubProvider {
+    pub fn new(name: &str, download: StubDownload) -> Arc<Self> {
+        Arc::new(Self {
+            name: name.into(),
+            failing: false,
+            download,
+            running: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+        })
+    }
+
+    pub fn failing(name: &str) -> Arc<Self> {
+        Arc::new(Self {
+            failing: true,
+            ..Arc::into_inner(Self::new(name, StubDownload::Fail)).unwrap()
+        })
+    }
+
+    fn check(&self) -> anyhow::Result<()> {
+        if self.failing {
+            Err(anyhow!("unavailable"))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[async_trait]
+impl CommunityProvider for StubProvider {
+    fn provider_name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn as_any(&self) -> &dyn std::any::Any {
+        self
+    }
+
+    fn into_any_arc(self: Arc<Self>) -> Arc<dyn std::any::Any + Send + Sync> {
+        self
+    }
+
+    async fn refresh(&self, _cfg: &str) -> anyhow::Result<()> {
+        self.check()
+    }
+
+    fn state(&self) -> ProviderState {
+        if self.failing {
+            ProviderState::Failed("unavailable".into())
+        } else {
+            ProviderState::Ready
+        }
+    }
+
+    async fn get_page(
+        &self,
+        _page: u32,
+        _limit: u32,
+        _search: SearchConfig,
+    ) -> anyhow::Result<Vec<ManifestItemV2>> {
+        self.check().map(|_| Vec::new())
+    }
+
+    async fn get_categories(&self) -> anyhow::Result<Vec<String>> {
+        self.check().map(|_| Vec::new())
+    }
+
+    async fn get_item_manifest(&self, _item_id: String) -> anyhow::Result<ManifestV2> {
+        Err(anyhow!("unavailable"))
+    }
+
+    async fn download(
+        &self,
+        item_id: String,
+        _device: String,
+        progress_cb: Option<Box<dyn Fn(ProgressData) + Send>>,
+    ) -> anyhow::Result<PathBuf> {
+        let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(running, Ordering::SeqCst);
+        let result = match &self.download {
+            StubDownload::Sleep(delay) => {
+                tokio::time::sleep(*delay).await;
+                Ok(PathBuf::from(&item_id))
+            }
+            StubDownload::Fail => Err(anyhow!("boom")),
+            StubDownload::File { url, dir } => crate::download::download_to_file(
+                &reqwest::Client::new(),
+                url,
+                &dir.join(format!("{item_id}.bin")),
+                Default::default(),
+                progress_cb,
+            )
+            .await
+            .map(|file| file.path),
+        };
+        self.running.fetch_sub(1, Ordering::SeqCst);
+        result
+    }
+
+    async fn get_total_items(&self) -> anyhow::Result<u64> {
+        self.check().map(|_| 0)
+    }
+}
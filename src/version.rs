@@ -0,0 +1,109 @@
+// 版本号比较规则：
+// - 去除首尾空白与 `v` / `V` 前缀，`+` 之后的构建元数据忽略；
+// - 核心部分按 `.` 切分逐段按数字比较（1.10 > 1.9），段数不足补 0（1.2 == 1.2.0）；
+// - 数字段相同时带 `-` 预发布后缀的版本较旧（1.0.0-beta < 1.0.0）；
+// - 任一方无法解析时不做大小判断，由调用方决定回退策略。
+use std::cmp::Ordering;
+
+// 宽松的版本号解析：容忍 `v` 前缀与 `-beta` / `+build` 后缀，数字段缺省补 0
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Version {
+    parts: Vec<u64>,
+    pre_release: bool,
+}
+
+fn parse(raw: &str) -> Option<Version> {
+    let raw = raw.trim();
+    let raw = raw
+        .strip_prefix('v')
+        .or_else(|| raw.strip_prefix('V'))
+        .unwrap_or(raw);
+    // 构建元数据不参与比较
+    let raw = raw.split('+').next().unwrap_or(raw);
+    let (core, pre_release) = match raw.split_once('-') {
+        Some((core, _)) => (core, true),
+        None => (raw, false),
+    };
+    if core.is_empty() {
+        return None;
+    }
+
+    let parts = core
+        .split('.')
+        .map(|part| part.parse::<u64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    Some(Version { parts, pre_release })
+}
+
+// 两个版本号均可解析时返回比较结果，否则返回 None
+pub fn compare(a: &str, b: &str) -> Option<Ordering> {
+    let (a, b) = (parse(a)?, parse(b)?);
+    let len = a.parts.len().max(b.parts.len());
+    for i in 0..len {
+        let x = a.parts.get(i).copied().unwrap_or(0);
+        let y = b.parts.get(i).copied().unwrap_or(0);
+        match x.cmp(&y) {
+            Ordering::Equal => continue,
+            other => return Some(other),
+        }
+    }
+    // 数字段相同时预发布版本较旧：1.0.0-beta < 1.0.0
+    Some(b.pre_release.cmp(&a.pre_release))
+}
+
+// 远端版本是否比本地新；无法解析时只要两者不同即视为有更新（仓库只发布当前版本）
+pub fn is_newer(remote: &str, local: &str) -> bool {
+    match compare(remote, local) {
+        Some(ord) => ord == Ordering::Greater,
+        None => remote.trim() != local.trim(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numeric_segments_compare_as_numbers() {
+        assert!(is_newer("1.10", "1.9"));
+        assert!(!is_newer("1.9", "1.10"));
+        assert!(is_newer("1.2.10", "1.2.9"));
+    }
+
+    #[test]
+    fn v_prefix_is_ignored() {
+        assert!(!is_newer("v1.2.0", "1.2.0"));
+        assert!(!is_newer("1.2.0", "V1.2.0"));
+        assert!(is_newer("v1.3", "1.2.9"));
+    }
+
+    #[test]
+    fn build_suffix_is_ignored() {
+        assert!(!is_newer("1.2.0+build.7", "1.2.0"));
+        assert!(!is_newer("1.2.0+20240101", "1.2.0+20231231"));
+        assert!(is_newer("1.2.1+1", "1.2.0+99"));
+    }
+
+    #[test]
+    fn extra_segments_are_newer() {
+        assert!(is_newer("2.3.1.100", "2.3.1"));
+        assert!(!is_newer("2.3.1", "2.3.1.100"));
+        assert!(!is_newer("2.3.1.0", "2.3.1"));
+    }
+
+    #[test]
+    fn messy_repo_versions() {
+        assert_eq!(compare("V1.0", "1.0.0"), Some(Ordering::Equal));
+        assert_eq!(compare("1.0.0-beta", "1.0.0"), Some(Ordering::Less));
+        assert!(is_newer("1.0.0", "1.0.0-beta"));
+        assert!(!is_newer("1.2.0 ", " v1.2.0"));
+        assert!(is_newer(" 1.2.1\t", "1.2.0"));
+    }
+
+    #[test]
+    fn unparsable_versions_fall_back_to_inequality() {
+        assert_eq!(compare("nightly", "1.0"), None);
+        assert!(is_newer("nightly", "1.0"));
+        assert!(!is_newer("nightly ", "nightly"));
+    }
+}